@@ -5,7 +5,7 @@ async fn main() -> color_eyre::eyre::Result<()> {
     color_eyre::install()?;
 
     let auth = DirectGrantAuth::new(
-        "admin-cli".into(),
+        "admin-cli",
         None,
         &std::env::var("KEYCLOAK_USERNAME")?,
         &std::env::var("KEYCLOAK_PASSWORD")?,
@@ -37,7 +37,9 @@ async fn main() -> color_eyre::eyre::Result<()> {
 
 async fn test(client: &keycloak_api::rest::Client) -> Result<(), keycloak_api::Error> {
     client
-        .get_realm("this_realm_is_invalid_and_does_not_exist")
+        .get_realm()
+        .realm("this_realm_is_invalid_and_does_not_exist")
+        .send()
         .await
         .map_err(keycloak_api::error::progenitor)?;
     Ok(())
@@ -4,11 +4,18 @@ use openapiv3::OpenAPI;
 
 fn main() {
     let src = find_spec_path();
+    emit_spec_info();
     let file = File::open(src).unwrap();
     let mut spec = serde_json::from_reader(file).unwrap();
     fix_spec(&mut spec);
 
-    let mut generator = progenitor::Generator::default();
+    // the builder interface allows setting only the required query parameters instead of passing
+    // a long list of positional `None`s
+    let mut generator = progenitor::Generator::new(
+        progenitor::GenerationSettings::default()
+            .with_interface(progenitor::InterfaceStyle::Builder)
+            .with_tag(progenitor::TagStyle::Merged),
+    );
     let tokens = generator.generate_tokens(&spec).unwrap();
     let ast = syn::parse2(tokens).unwrap();
     let content = prettyplease::unparse(&ast);
@@ -40,6 +47,38 @@ fn find_spec_path() -> std::path::PathBuf {
     );
 }
 
+// make information about the used spec available to the crate (see `rest::client_spec_info`)
+fn emit_spec_info() {
+    // `find_spec_path` only watches KEYCLOAK_VERSION if OPENAPI_SPEC_PATH isn't set, but the
+    // version is reported either way
+    println!("cargo:rerun-if-env-changed=KEYCLOAK_VERSION");
+    println!("cargo:rerun-if-env-changed=KEYCLOAK_REVISION");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let version = std::env::var("KEYCLOAK_VERSION").unwrap_or_else(|_| {
+        // a custom spec path doesn't tell us anything about the version
+        if std::env::var("OPENAPI_SPEC_PATH").is_ok() {
+            "unknown".into()
+        } else {
+            "unstable".into()
+        }
+    });
+    println!("cargo:rustc-env=KEYCLOAK_API_SPEC_VERSION={version}");
+    if let Ok(revision) = std::env::var("KEYCLOAK_REVISION") {
+        println!("cargo:rustc-env=KEYCLOAK_API_SPEC_REVISION={revision}");
+    }
+    // respect SOURCE_DATE_EPOCH for reproducible builds
+    let generated_at = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        });
+    println!("cargo:rustc-env=KEYCLOAK_API_GENERATED_AT={generated_at}");
+}
+
 // all of this is just one giant hack to make sure this works
 // the generated openapi spec doesn't match the keycloak api and the rust generator doesn't support all openapi features...
 fn fix_spec(spec: &mut OpenAPI) {
@@ -51,9 +90,87 @@ fn fix_spec(spec: &mut OpenAPI) {
         fix_operation(&mut item.put, "put", path);
         fix_operation(&mut item.delete, "delete", path);
 
-        if path == "/admin/realms/{realm}/clients/{client-uuid}/authz/resource-server/policy/by-type/{policy-type}/{policy-id}" {
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/clients/{client-uuid}/authz/resource-server/policy/by-type/{policy-type}/{policy-id}"
+                | "/admin/realms/{realm}/clients/{client-uuid}/authz/resource-server/permission/by-type/{policy-type}/{policy-id}"
+        ) {
             fix_stringly_typed_json_body(&mut item.put);
         }
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/clients/{client-uuid}/authz/resource-server/policy/by-type/{policy-type}"
+                | "/admin/realms/{realm}/clients/{client-uuid}/authz/resource-server/permission/by-type/{policy-type}"
+        ) {
+            fix_stringly_typed_json_body(&mut item.post);
+            // keycloak responds with the created policy
+            fix_response_schema(&mut item.post, 201, "AbstractPolicyRepresentation");
+        }
+        // keycloak responds with `201 Created` and the new resource's url in the `Location` header
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/users"
+                | "/admin/realms/{realm}/groups"
+                | "/admin/realms/{realm}/groups/{group-id}/children"
+                | "/admin/realms/{realm}/roles"
+                | "/admin/realms/{realm}/clients/{client-uuid}/roles"
+                | "/admin/realms/{realm}/clients/{client-uuid}/protocol-mappers/models"
+                | "/admin/realms/{realm}/components"
+        ) {
+            fix_created_response(&mut item.post);
+        }
+        // posting an existing group moves it instead and keycloak responds with `204 No Content`
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/groups" | "/admin/realms/{realm}/groups/{group-id}/children"
+        ) {
+            add_no_content_response(&mut item.post);
+        }
+        // keycloak responds with `204 No Content`
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/users/{user-id}/execute-actions-email"
+                | "/admin/realms/{realm}/users/{user-id}/send-verify-email"
+        ) {
+            fix_no_content_response(&mut item.put);
+        }
+        // updates respond with `204 No Content` as well. the generated builders (e.g.
+        // `PutRealmUser`) only accept the status codes listed here, anything else ends up as
+        // `Error::UnexpectedResponse` even though the update succeeded.
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}"
+                | "/admin/realms/{realm}/users/{user-id}"
+                | "/admin/realms/{realm}/groups/{group-id}"
+                | "/admin/realms/{realm}/clients/{client-uuid}"
+                | "/admin/realms/{realm}/components/{id}"
+        ) {
+            fix_no_content_response(&mut item.put);
+        }
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/users/{user-id}"
+                | "/admin/realms/{realm}/clients/{client-uuid}/client-secret/rotated"
+        ) {
+            fix_no_content_response(&mut item.delete);
+        }
+        if matches!(
+            path.as_str(),
+            "/admin/realms/{realm}/users/{user-id}/federated-identity/{provider}"
+        ) {
+            fix_no_content_response(&mut item.post);
+            fix_missing_request_body(&mut item.post, "FederatedIdentityRepresentation");
+        }
+        // keycloak responds with the created token, not the create request
+        if path == "/admin/realms/{realm}/clients-initial-access" {
+            fix_response_schema(&mut item.post, 201, "ClientInitialAccessPresentation");
+        }
+        if path == "/admin/realms" {
+            fix_request_body_schema(&mut item.post, "RealmRepresentation");
+        }
+        if path == "/admin/realms/{realm}/partial-export" {
+            fix_response_schema(&mut item.post, 200, "RealmRepresentation");
+        }
     }
 
     let components = spec.components.as_mut().unwrap();
@@ -218,6 +335,88 @@ fn fix_stringly_typed_json_body(operation: &mut Option<openapiv3::Operation>) {
         }));
 }
 
+// some create operations are specified to return `200 OK`, but keycloak actually returns `201 Created`
+// the generated client treats every undocumented status code as an error, so fix the status here
+fn fix_created_response(operation: &mut Option<openapiv3::Operation>) {
+    let op = operation.as_mut().unwrap();
+    let responses = &mut op.responses.responses;
+    if let Some(response) = responses.swap_remove(&openapiv3::StatusCode::Code(200)) {
+        responses.insert(openapiv3::StatusCode::Code(201), response);
+    }
+}
+
+// some operations without a response body are specified to return `200 OK`, but keycloak actually
+// returns `204 No Content`
+fn add_no_content_response(operation: &mut Option<openapiv3::Operation>) {
+    let op = operation.as_mut().unwrap();
+    op.responses.responses.insert(
+        openapiv3::StatusCode::Code(204),
+        openapiv3::ReferenceOr::Item(openapiv3::Response {
+            description: "No Content".into(),
+            ..Default::default()
+        }),
+    );
+}
+fn fix_no_content_response(operation: &mut Option<openapiv3::Operation>) {
+    let op = operation.as_mut().unwrap();
+    let responses = &mut op.responses.responses;
+    if let Some(response) = responses.swap_remove(&openapiv3::StatusCode::Code(200)) {
+        responses.insert(openapiv3::StatusCode::Code(204), response);
+    }
+}
+
+// some operations are specified with a wrong or missing response body
+fn fix_response_schema(operation: &mut Option<openapiv3::Operation>, status: u16, schema: &str) {
+    let op = operation.as_mut().unwrap();
+    let Some(openapiv3::ReferenceOr::Item(response)) = op
+        .responses
+        .responses
+        .get_mut(&openapiv3::StatusCode::Code(status))
+    else {
+        return;
+    };
+    response.content.insert(
+        "application/json".to_string(),
+        openapiv3::MediaType {
+            schema: Some(openapiv3::ReferenceOr::ref_(&format!(
+                "#/components/schemas/{schema}"
+            ))),
+            ..Default::default()
+        },
+    );
+}
+
+// some operations are specified to take a binary string, but keycloak actually expects json
+fn fix_request_body_schema(operation: &mut Option<openapiv3::Operation>, schema: &str) {
+    let op = operation.as_mut().unwrap();
+    let body = op.request_body.as_mut().unwrap().get_item_mut().unwrap();
+    let json_body = body.content.get_mut("application/json").unwrap();
+    json_body.schema = Some(openapiv3::ReferenceOr::ref_(&format!(
+        "#/components/schemas/{schema}"
+    )));
+}
+
+// some operations are specified without a request body, but keycloak actually expects one
+fn fix_missing_request_body(operation: &mut Option<openapiv3::Operation>, schema: &str) {
+    let op = operation.as_mut().unwrap();
+    if op.request_body.is_some() {
+        return;
+    }
+    let media_type = openapiv3::MediaType {
+        schema: Some(openapiv3::ReferenceOr::ref_(&format!(
+            "#/components/schemas/{schema}"
+        ))),
+        ..Default::default()
+    };
+    op.request_body = Some(openapiv3::ReferenceOr::Item(openapiv3::RequestBody {
+        content: [("application/json".to_string(), media_type)]
+            .into_iter()
+            .collect(),
+        required: true,
+        ..Default::default()
+    }));
+}
+
 trait RefOrExt<T> {
     fn get_item_mut(&mut self) -> Option<&mut T>;
 }
@@ -231,16 +430,6 @@ impl<T> RefOrExt<T> for openapiv3::ReferenceOr<T> {
     }
 }
 
-trait PathExt {
-    fn get_path_item_mut(&mut self, path: &str) -> Option<&mut openapiv3::PathItem>;
-}
-
-impl PathExt for openapiv3::Paths {
-    fn get_path_item_mut(&mut self, path: &str) -> Option<&mut openapiv3::PathItem> {
-        self.paths.get_mut(path).and_then(RefOrExt::get_item_mut)
-    }
-}
-
 trait ParameterExt {
     fn parameter_data_mut(&mut self) -> &mut openapiv3::ParameterData;
 }
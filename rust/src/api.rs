@@ -1,13 +1,95 @@
 //! this module (or rather, its submodules) implements the keycloak api using extension traits so
 //! we have less clutter
 
+pub mod analysis;
+pub mod authentication;
 pub mod client;
+pub mod component;
+pub mod event;
 pub mod group;
+pub mod identity_provider;
+pub mod organization;
 pub mod realm;
 pub mod role;
 pub mod user;
 
 pub use self::{
-    client::KeycloakClientExt, group::KeycloakGroupExt, realm::KeycloakRealmExt,
-    role::KeycloakRoleExt, user::KeycloakUserExt,
+    analysis::KeycloakAnalysisExt,
+    authentication::KeycloakAuthenticationExt,
+    client::KeycloakClientExt,
+    component::KeycloakComponentExt,
+    event::KeycloakEventExt,
+    group::KeycloakGroupExt,
+    identity_provider::KeycloakIdentityProviderExt,
+    organization::KeycloakOrganizationExt,
+    realm::KeycloakRealmExt,
+    role::{KeycloakRoleExt, RoleMembership},
+    user::KeycloakUserExt,
 };
+
+use crate::{rest::types::GroupRepresentation, Error, ErrorKind};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// result of the idempotent `ensure_*` methods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ensured {
+    /// the resource didn't exist and was created with the given id
+    Created(String),
+    /// the resource already existed and was updated, it has the given id
+    Updated(String),
+}
+
+impl Ensured {
+    /// id of the created or updated resource
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Created(id) | Self::Updated(id) => id,
+        }
+    }
+
+    pub fn created(&self) -> bool {
+        matches!(self, Self::Created(_))
+    }
+}
+
+/// get the id of a newly created resource from the `Location` header of the response
+fn id_from_location<T>(response: &progenitor_client::ResponseValue<T>) -> Result<String> {
+    response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .and_then(|location| location.trim_end_matches('/').rsplit('/').next())
+        .filter(|id| !id.is_empty())
+        .map(Into::into)
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))
+}
+
+/// convert a count returned by keycloak (signed in the api spec) to `u64`
+fn count_from<T: TryInto<u64> + ToString + Copy>(count: T) -> Result<u64> {
+    count
+        .try_into()
+        .map_err(|_| Error::new_kind(ErrorKind::WrongType("count".into(), count.to_string())))
+}
+
+async fn top_level_groups<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+) -> Result<Vec<GroupRepresentation>> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!("querying groups");
+    let groups = paginate_api!(|first, max| {
+        api_client
+            .get_realm_groups()
+            .realm(&keycloak.config.realm)
+            .brief_representation(false)
+            .first(first)
+            .max(max)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner()
+    });
+    Ok(groups)
+}
@@ -0,0 +1,648 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+
+use super::{KeycloakClientExt, KeycloakGroupExt, KeycloakRoleExt};
+use crate::{
+    rest::types::{
+        AbstractPolicyRepresentation, ClientRepresentation, GroupRepresentation,
+        ResourceRepresentation, RoleRepresentation, ScopeRepresentation,
+    },
+    Error,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// realm-wide analysis helpers built on top of the other api methods
+pub trait KeycloakAnalysisExt {
+    /// find unused roles and empty groups
+    ///
+    /// this queries every role and group of the realm (including client roles) and counts their
+    /// members, so it takes a while for larger realms.
+    fn access_report(&self) -> impl Future<Output = Result<AccessReport>> + Send;
+
+    /// get the authorization model of a client with the relations between its policies,
+    /// permissions, resources and scopes
+    ///
+    /// this queries the relations of every policy separately, so it takes a while for clients
+    /// with many policies.
+    #[cfg(feature = "unstable")]
+    fn client_authz_graph(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<AuthzGraph>> + Send;
+}
+
+/// result of [`access_report`](KeycloakAnalysisExt::access_report)
+#[derive(Debug, Clone, Default)]
+pub struct AccessReport {
+    /// realm and client roles without direct or indirect members
+    ///
+    /// indirect members are members of groups with the role (including members of subgroups)
+    /// and members of composite roles containing the role
+    pub unused_roles: Vec<RoleRepresentation>,
+    /// groups without role mappings whose subgroups (if any) don't have members either
+    pub empty_groups: Vec<GroupRepresentation>,
+}
+
+/// result of [`client_authz_graph`](KeycloakAnalysisExt::client_authz_graph)
+#[derive(Debug, Clone, Default)]
+pub struct AuthzGraph {
+    /// all policies and permissions of the client
+    pub nodes: Vec<AuthzGraphNode>,
+}
+
+/// a policy or permission and its relations
+#[derive(Debug, Clone)]
+pub struct AuthzGraphNode {
+    pub policy: AbstractPolicyRepresentation,
+    /// ids of the policies this policy depends on
+    pub associated_policies: Vec<String>,
+    /// resources a permission applies to
+    pub resources: Vec<ResourceRepresentation>,
+    /// scopes a permission applies to
+    pub scopes: Vec<ScopeRepresentation>,
+}
+
+impl AuthzGraph {
+    /// get a policy or permission given its id
+    pub fn node(&self, policy_id: &str) -> Option<&AuthzGraphNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.policy.id.as_deref() == Some(policy_id))
+    }
+
+    /// ids of the policies and permissions directly depending on a policy
+    pub fn dependents(&self, policy_id: &str) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|node| node.associated_policies.iter().any(|id| id == policy_id))
+            .filter_map(|node| node.policy.id.as_deref())
+            .collect()
+    }
+
+    /// policies not used by any permission, neither directly nor through other policies
+    pub fn unused_policies(&self) -> Vec<&AuthzGraphNode> {
+        let mut used = HashSet::new();
+        let mut pending: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| is_permission(&node.policy))
+            .flat_map(|node| node.associated_policies.iter().map(String::as_str))
+            .collect();
+        while let Some(policy_id) = pending.pop() {
+            if !used.insert(policy_id) {
+                continue;
+            }
+            if let Some(node) = self.node(policy_id) {
+                pending.extend(node.associated_policies.iter().map(String::as_str));
+            }
+        }
+        self.nodes
+            .iter()
+            .filter(|node| !is_permission(&node.policy))
+            .filter(|node| {
+                !node
+                    .policy
+                    .id
+                    .as_deref()
+                    .is_some_and(|id| used.contains(id))
+            })
+            .collect()
+    }
+}
+
+fn is_permission(policy: &AbstractPolicyRepresentation) -> bool {
+    matches!(policy.type_.as_deref(), Some("resource" | "scope"))
+}
+
+/// result of [`diff_clients`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientDiff {
+    /// differing fields, sorted by path
+    pub fields: Vec<FieldDiff>,
+}
+
+impl ClientDiff {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// a field differing between two clients
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// name of the field, `attributes.<key>` for attributes and `protocolMappers.<name>` for
+    /// protocol mappers
+    pub path: String,
+    /// value of the actual client, `None` if unset
+    pub actual: Option<serde_json::Value>,
+    /// value of the desired client, `None` if unset
+    pub desired: Option<serde_json::Value>,
+}
+
+/// fields managed by keycloak or specific to a single environment
+const IGNORED_CLIENT_FIELDS: [&str; 5] = [
+    "id",
+    "access",
+    "secret",
+    "registrationAccessToken",
+    "authorizationSettings",
+];
+
+/// attributes managed by keycloak
+const IGNORED_CLIENT_ATTRIBUTES: [&str; 1] = ["client.secret.creation.time"];
+
+/// compare two clients, e.g. the same client in different environments
+///
+/// server-managed fields (ids, the secret and its creation time) are ignored, lists of strings
+/// are compared regardless of their order and protocol mappers are matched by name. the
+/// authorization settings are not compared, use
+/// [`client_authz_export`](super::KeycloakClientExt::client_authz_export) for those.
+pub fn diff_clients(
+    actual: &ClientRepresentation,
+    desired: &ClientRepresentation,
+) -> Result<ClientDiff> {
+    let actual = flatten_client(actual)?;
+    let mut desired = flatten_client(desired)?;
+
+    let mut fields = Vec::new();
+    for (path, actual) in actual {
+        let desired = desired.remove(&path);
+        if desired.as_ref() != Some(&actual) {
+            fields.push(FieldDiff {
+                path,
+                actual: Some(actual),
+                desired,
+            });
+        }
+    }
+    fields.extend(desired.into_iter().map(|(path, desired)| FieldDiff {
+        path,
+        actual: None,
+        desired: Some(desired),
+    }));
+    fields.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ClientDiff { fields })
+}
+
+/// normalize a client to a map of comparable fields
+fn flatten_client(client: &ClientRepresentation) -> Result<BTreeMap<String, serde_json::Value>> {
+    let serde_json::Value::Object(client) =
+        serde_json::to_value(client).map_err(crate::error::deserialize)?
+    else {
+        return Err(Error::new_kind(crate::ErrorKind::WrongType(
+            "object".into(),
+            "non-object".into(),
+        )));
+    };
+
+    let mut fields = BTreeMap::new();
+    for (key, value) in client {
+        if IGNORED_CLIENT_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        match value {
+            serde_json::Value::Object(map) => {
+                for (field, value) in map {
+                    if key == "attributes" && IGNORED_CLIENT_ATTRIBUTES.contains(&field.as_str()) {
+                        continue;
+                    }
+                    fields.insert(format!("{key}.{field}"), value);
+                }
+            }
+            serde_json::Value::Array(mappers) if key == "protocolMappers" => {
+                for mut mapper in mappers {
+                    let name = mapper["name"].as_str().unwrap_or_default().to_string();
+                    if let Some(mapper) = mapper.as_object_mut() {
+                        mapper.remove("id");
+                    }
+                    fields.insert(format!("{key}.{name}"), mapper);
+                }
+            }
+            serde_json::Value::Array(mut values) if values.iter().all(|v| v.is_string()) => {
+                values.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                values.dedup();
+                fields.insert(key, serde_json::Value::Array(values));
+            }
+            value => {
+                fields.insert(key, value);
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// a role with everything needed to decide whether it is used
+struct RoleUsage {
+    role: RoleRepresentation,
+    /// client id (not the uuid) of client roles
+    client_id: Option<String>,
+    /// number of direct members
+    members: usize,
+    /// ids of the roles contained in this role
+    composites: Vec<String>,
+}
+
+/// a group of the flattened group tree
+struct GroupNode {
+    group: GroupRepresentation,
+    /// index of the parent group, parents always come before their subgroups
+    parent: Option<usize>,
+    /// number of direct members
+    members: usize,
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakAnalysisExt for crate::Keycloak<A> {
+    #[tracing::instrument(skip(self))]
+    async fn access_report(&self) -> Result<AccessReport> {
+        let clients = self.clients().await?;
+
+        let mut roles = Vec::new();
+        for role in self.realm_roles(None, None).await? {
+            roles.push(role_usage(self, role, None).await?);
+        }
+        for client in &clients {
+            let (Some(uuid), Some(client_id)) = (&client.id, &client.client_id) else {
+                continue;
+            };
+            for role in self.client_roles(uuid, None, None).await? {
+                roles.push(role_usage(self, role, Some(client_id)).await?);
+            }
+        }
+
+        let mut groups = Vec::new();
+        let mut stack: Vec<_> = super::top_level_groups(self)
+            .await?
+            .into_iter()
+            .rev()
+            .map(|group| (group, None))
+            .collect();
+        while let Some((mut group, parent)) = stack.pop() {
+            let Some(group_id) = group.id.clone() else {
+                continue;
+            };
+            let subgroups = if group.sub_group_count.unwrap_or_default() > 0 {
+                self.all_group_children(&group_id).await?
+            } else {
+                std::mem::take(&mut group.sub_groups)
+            };
+            let members = count_group_members(self, &group_id).await?;
+            let index = groups.len();
+            groups.push(GroupNode {
+                group,
+                parent,
+                members,
+            });
+            stack.extend(subgroups.into_iter().rev().map(|g| (g, Some(index))));
+        }
+
+        Ok(build_report(roles, groups))
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_graph(&self, client_uuid: &str) -> Result<AuthzGraph> {
+        let mut policies = self.client_authz_policies(client_uuid).await?;
+        // depending on the keycloak version, permissions are also listed as policies
+        let known: HashSet<String> = policies.iter().filter_map(|p| p.id.clone()).collect();
+        policies.extend(
+            self.client_authz_permissions(client_uuid)
+                .await?
+                .into_iter()
+                .filter(|p| !p.id.as_ref().is_some_and(|id| known.contains(id))),
+        );
+
+        let mut nodes = Vec::with_capacity(policies.len());
+        for policy in policies {
+            let Some(policy_id) = policy.id.clone() else {
+                continue;
+            };
+            let associated_policies = self
+                .client_authz_policy_associated_policies(client_uuid, &policy_id)
+                .await?
+                .into_iter()
+                .filter_map(|p| p.id)
+                .collect();
+            let resources = self
+                .client_authz_policy_resources(client_uuid, &policy_id)
+                .await?;
+            let scopes = self
+                .client_authz_policy_scopes(client_uuid, &policy_id)
+                .await?;
+            nodes.push(AuthzGraphNode {
+                policy,
+                associated_policies,
+                resources,
+                scopes,
+            });
+        }
+        Ok(AuthzGraph { nodes })
+    }
+}
+
+fn build_report(roles: Vec<RoleUsage>, groups: Vec<GroupNode>) -> AccessReport {
+    // members of subgroups inherit the roles of their parents, so a group counts as populated if
+    // any group in its subtree has members
+    let mut populated: Vec<bool> = groups.iter().map(|g| g.members > 0).collect();
+    for (index, group) in groups.iter().enumerate().rev() {
+        if let (true, Some(parent)) = (populated[index], group.parent) {
+            populated[parent] = true;
+        }
+    }
+
+    let has_role = |group: &GroupRepresentation, usage: &RoleUsage| {
+        let Some(name) = &usage.role.name else {
+            return false;
+        };
+        match &usage.client_id {
+            Some(client_id) => group
+                .client_roles
+                .get(client_id)
+                .is_some_and(|roles| roles.contains(name)),
+            None => group.realm_roles.contains(name),
+        }
+    };
+    let mut used: HashSet<String> = roles
+        .iter()
+        .filter(|usage| {
+            usage.members > 0
+                || groups
+                    .iter()
+                    .zip(&populated)
+                    .any(|(node, populated)| *populated && has_role(&node.group, usage))
+        })
+        .filter_map(|usage| usage.role.id.clone())
+        .collect();
+
+    // roles contained in used composite roles are used as well
+    let composites: HashMap<&str, &[String]> = roles
+        .iter()
+        .filter_map(|usage| Some((usage.role.id.as_deref()?, usage.composites.as_slice())))
+        .collect();
+    let mut pending: Vec<String> = used.iter().cloned().collect();
+    while let Some(role_id) = pending.pop() {
+        for child in composites
+            .get(role_id.as_str())
+            .copied()
+            .unwrap_or_default()
+        {
+            if used.insert(child.clone()) {
+                pending.push(child.clone());
+            }
+        }
+    }
+
+    let unused_roles = roles
+        .into_iter()
+        .filter(|usage| !usage.role.id.as_deref().is_some_and(|id| used.contains(id)))
+        .map(|usage| usage.role)
+        .collect();
+    let empty_groups = groups
+        .into_iter()
+        .zip(populated)
+        .filter(|(node, populated)| {
+            !populated && node.group.realm_roles.is_empty() && node.group.client_roles.is_empty()
+        })
+        .map(|(node, _)| node.group)
+        .collect();
+    AccessReport {
+        unused_roles,
+        empty_groups,
+    }
+}
+
+async fn role_usage<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    role: RoleRepresentation,
+    client_id: Option<&str>,
+) -> Result<RoleUsage> {
+    let (Some(role_id), Some(role_name)) = (role.id.as_deref(), role.name.as_deref()) else {
+        return Ok(RoleUsage {
+            role,
+            client_id: client_id.map(Into::into),
+            members: 0,
+            composites: Vec::new(),
+        });
+    };
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!(role_name, "counting role members");
+    let members = match (&role.client_role, &role.container_id) {
+        (Some(true), Some(client_uuid)) => count_api!(|first, max| {
+            api_client
+                .get_realm_client_role_by_name_users()
+                .realm(&keycloak.config.realm)
+                .client_uuid(client_uuid)
+                .role_name(role_name)
+                .brief_representation(true)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        }),
+        _ => count_api!(|first, max| {
+            api_client
+                .get_realm_role_by_name_users()
+                .realm(&keycloak.config.realm)
+                .role_name(role_name)
+                .brief_representation(true)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        }),
+    };
+
+    let composites = if role.composite == Some(true) {
+        tracing::debug!(role_name, "querying composite roles");
+        paginate_api!(|first, max| {
+            api_client
+                .get_realm_role_by_id_composites()
+                .realm(&keycloak.config.realm)
+                .role_id(role_id)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        })
+        .into_iter()
+        .filter_map(|role| role.id)
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(RoleUsage {
+        role,
+        client_id: client_id.map(Into::into),
+        members,
+        composites,
+    })
+}
+
+async fn count_group_members<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    group_id: &str,
+) -> Result<usize> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!(group_id, "counting group members");
+    let members = count_api!(|first, max| {
+        api_client
+            .get_realm_group_members()
+            .realm(&keycloak.config.realm)
+            .group_id(group_id)
+            .brief_representation(true)
+            .first(first)
+            .max(max)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner()
+    });
+    Ok(members)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AuthzGraph, AuthzGraphNode, FieldDiff, GroupNode, RoleUsage};
+
+    fn role(id: &str, composites: &[&str], members: usize) -> RoleUsage {
+        RoleUsage {
+            role: serde_json::from_value(serde_json::json!({ "id": id, "name": id })).unwrap(),
+            client_id: None,
+            members,
+            composites: composites.iter().map(|&c| c.into()).collect(),
+        }
+    }
+
+    fn group(id: &str, parent: Option<usize>, roles: &[&str], members: usize) -> GroupNode {
+        GroupNode {
+            group: serde_json::from_value(serde_json::json!({ "id": id, "realmRoles": roles }))
+                .unwrap(),
+            parent,
+            members,
+        }
+    }
+
+    #[test]
+    fn test_build_report() {
+        let roles = vec![
+            role("direct", &[], 1),
+            role("composite", &["via-composite"], 0),
+            role("via-composite", &[], 0),
+            role("via-subgroup", &[], 0),
+            role("empty-group", &[], 0),
+            role("unused", &["unused-child"], 0),
+            role("unused-child", &[], 0),
+        ];
+        let groups = vec![
+            group("parent", None, &["via-subgroup"], 0),
+            group("child", Some(0), &["composite"], 2),
+            group("structural", None, &[], 0),
+            group("empty", Some(2), &[], 0),
+            group("unpopulated", None, &["empty-group"], 0),
+        ];
+        let report = super::build_report(roles, groups);
+
+        let unused: Vec<_> = report
+            .unused_roles
+            .iter()
+            .map(|r| r.id.as_deref().unwrap())
+            .collect();
+        assert_eq!(unused, ["empty-group", "unused", "unused-child"]);
+        let empty: Vec<_> = report
+            .empty_groups
+            .iter()
+            .map(|g| g.id.as_deref().unwrap())
+            .collect();
+        assert_eq!(empty, ["structural", "empty"]);
+    }
+
+    fn node(id: &str, policy_type: &str, associated: &[&str]) -> AuthzGraphNode {
+        AuthzGraphNode {
+            policy: serde_json::from_value(serde_json::json!({ "id": id, "type": policy_type }))
+                .unwrap(),
+            associated_policies: associated.iter().map(|&a| a.into()).collect(),
+            resources: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_authz_graph() {
+        let graph = AuthzGraph {
+            nodes: vec![
+                node("permission", "scope", &["aggregate"]),
+                node("aggregate", "aggregate", &["role", "user"]),
+                node("role", "role", &[]),
+                node("user", "user", &[]),
+                node("unused", "role", &[]),
+                node("unused-aggregate", "aggregate", &["unused-user"]),
+                node("unused-user", "user", &[]),
+            ],
+        };
+        assert_eq!(graph.dependents("role"), ["aggregate"]);
+        assert_eq!(graph.dependents("aggregate"), ["permission"]);
+        assert!(graph.dependents("permission").is_empty());
+
+        let unused: Vec<_> = graph
+            .unused_policies()
+            .into_iter()
+            .filter_map(|node| node.policy.id.as_deref())
+            .collect();
+        assert_eq!(unused, ["unused", "unused-aggregate", "unused-user"]);
+    }
+
+    #[test]
+    fn test_diff_clients() {
+        let actual = serde_json::from_value(serde_json::json!({
+            "id": "uuid-1",
+            "clientId": "client",
+            "secret": "secret-1",
+            "redirectUris": ["https://a.example.com/*", "https://b.example.com/*"],
+            "attributes": {
+                "client.secret.creation.time": "1700000000",
+                "pkce.code.challenge.method": "S256",
+            },
+            "protocolMappers": [{ "id": "1", "name": "audience", "protocol": "openid-connect" }],
+        }))
+        .unwrap();
+        let desired = serde_json::from_value(serde_json::json!({
+            "id": "uuid-2",
+            "clientId": "client",
+            "secret": "secret-2",
+            "publicClient": true,
+            "redirectUris": ["https://b.example.com/*", "https://a.example.com/*"],
+            "attributes": { "client.secret.creation.time": "1800000000" },
+            "protocolMappers": [{ "id": "2", "name": "audience", "protocol": "openid-connect" }],
+        }))
+        .unwrap();
+
+        let diff = super::diff_clients(&actual, &desired).unwrap();
+        assert_eq!(
+            diff.fields,
+            [
+                FieldDiff {
+                    path: "attributes.pkce.code.challenge.method".into(),
+                    actual: Some("S256".into()),
+                    desired: None,
+                },
+                FieldDiff {
+                    path: "publicClient".into(),
+                    actual: None,
+                    desired: Some(true.into()),
+                },
+            ]
+        );
+        assert!(super::diff_clients(&actual, &actual).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,700 @@
+use std::{collections::HashMap, future::Future};
+
+use reqwest::StatusCode;
+
+use crate::{
+    error::ResourceType,
+    rest::{
+        types::{
+            AuthenticationExecutionInfoRepresentation, AuthenticationFlowRepresentation,
+            AuthenticatorConfigRepresentation, RequiredActionProviderRepresentation,
+        },
+        ExecutionRequirement, FlowType,
+    },
+    Error, ErrorKind,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// authentication-related methods of the keycloak api, i.e. authentication flows, their
+/// executions, authenticator configs and required actions
+pub trait KeycloakAuthenticationExt {
+    /// get the top-level authentication flows of the realm
+    fn authentication_flows(
+        &self,
+    ) -> impl Future<Output = Result<Vec<AuthenticationFlowRepresentation>>> + Send;
+
+    /// get a single authentication flow given its id
+    fn authentication_flow(
+        &self,
+        flow_id: &str,
+    ) -> impl Future<Output = Result<AuthenticationFlowRepresentation>> + Send;
+
+    /// get a single top-level authentication flow given its alias
+    fn authentication_flow_by_alias(
+        &self,
+        alias: &str,
+    ) -> impl Future<Output = Result<AuthenticationFlowRepresentation>> + Send;
+
+    /// create a top-level authentication flow, returning its id
+    ///
+    /// fails with [`ErrorKind::Conflict`] if a flow with the same alias already exists
+    fn create_authentication_flow(
+        &self,
+        flow: &AuthenticationFlowRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// delete an authentication flow
+    ///
+    /// built-in flows and flows still bound to the realm or a client can't be deleted
+    fn delete_authentication_flow(&self, flow_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the executions of an authentication flow, including those of its sub-flows
+    ///
+    /// the executions are ordered as shown in the admin console, with `level` giving the depth
+    /// of sub-flow executions
+    fn authentication_flow_executions(
+        &self,
+        flow_alias: &str,
+    ) -> impl Future<Output = Result<Vec<AuthenticationExecutionInfoRepresentation>>> + Send;
+
+    /// copy an authentication flow (e.g. a built-in one) including its executions
+    ///
+    /// fails with [`ErrorKind::Conflict`] if a flow named `new_name` already exists
+    fn copy_authentication_flow(
+        &self,
+        flow_alias: &str,
+        new_name: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// add an execution of the authenticator `provider` to a flow, returning the execution's id
+    ///
+    /// new executions are added at the end of the flow and are disabled
+    fn add_authentication_execution(
+        &self,
+        flow_alias: &str,
+        provider: &str,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// add a sub-flow to a flow, returning the id of the new flow
+    ///
+    /// the execution containing the sub-flow is added at the end of the flow and is disabled
+    fn add_authentication_subflow(
+        &self,
+        flow_alias: &str,
+        alias: &str,
+        flow_type: &FlowType,
+        description: Option<&str>,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// move an execution up within its flow
+    fn raise_authentication_execution_priority(
+        &self,
+        execution_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// move an execution down within its flow
+    fn lower_authentication_execution_priority(
+        &self,
+        execution_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// set the requirement of an execution of the flow `flow_alias` (or of one of its sub-flows)
+    fn set_authentication_execution_requirement(
+        &self,
+        flow_alias: &str,
+        execution_id: &str,
+        requirement: ExecutionRequirement,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get an authenticator config given its id
+    ///
+    /// the id of an execution's config is its `authentication_config`
+    fn authenticator_config(
+        &self,
+        config_id: &str,
+    ) -> impl Future<Output = Result<AuthenticatorConfigRepresentation>> + Send;
+
+    /// create the authenticator config of an execution, returning the config's id
+    fn create_authenticator_config(
+        &self,
+        execution_id: &str,
+        config: &AuthenticatorConfigRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// update an authenticator config
+    fn update_authenticator_config(
+        &self,
+        config_id: &str,
+        config: &AuthenticatorConfigRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete an authenticator config, detaching it from its execution
+    fn delete_authenticator_config(
+        &self,
+        config_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the registered required actions of the realm, ordered by priority
+    fn required_actions(
+        &self,
+    ) -> impl Future<Output = Result<Vec<RequiredActionProviderRepresentation>>> + Send;
+
+    /// get a single registered required action given its alias
+    fn required_action(
+        &self,
+        alias: &str,
+    ) -> impl Future<Output = Result<RequiredActionProviderRepresentation>> + Send;
+
+    /// get the required action providers which aren't registered in the realm yet
+    ///
+    /// each entry contains the `providerId` and `name` to pass to
+    /// [`register_required_action`](Self::register_required_action)
+    fn unregistered_required_actions(
+        &self,
+    ) -> impl Future<Output = Result<Vec<HashMap<String, String>>>> + Send;
+
+    /// register the required action provider `provider_id` in the realm
+    ///
+    /// the alias of the registered action is `provider_id`
+    fn register_required_action(
+        &self,
+        provider_id: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// update a registered required action, e.g. whether it is enabled, a default action or its
+    /// priority
+    fn update_required_action(
+        &self,
+        alias: &str,
+        action: &RequiredActionProviderRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// modify a registered required action
+    ///
+    /// this fetches the current action, applies `modify` and writes the result back. returns the
+    /// updated action.
+    fn modify_required_action<F>(
+        &self,
+        alias: &str,
+        modify: F,
+    ) -> impl Future<Output = Result<RequiredActionProviderRepresentation>> + Send
+    where
+        F: FnOnce(&mut RequiredActionProviderRepresentation) + Send;
+
+    /// unregister a required action
+    fn delete_required_action(&self, alias: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakAuthenticationExt
+    for crate::Keycloak<A>
+{
+    #[tracing::instrument(skip(self))]
+    async fn authentication_flows(&self) -> Result<Vec<AuthenticationFlowRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying authentication flows");
+        let response = api_client
+            .get_realm_authentication_flows()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authentication_flow(&self, flow_id: &str) -> Result<AuthenticationFlowRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying authentication flow");
+        let response = api_client
+            .get_realm_authentication_flows_id()
+            .realm(&self.config.realm)
+            .id(flow_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authentication_flow_by_alias(
+        &self,
+        alias: &str,
+    ) -> Result<AuthenticationFlowRepresentation> {
+        self.authentication_flows()
+            .await?
+            .into_iter()
+            .find(|flow| flow.alias.as_deref() == Some(alias))
+            .ok_or_else(|| Error::new_kind(ErrorKind::NotFound(ResourceType::AuthenticationFlow)))
+    }
+
+    #[tracing::instrument(skip(self, flow), fields(alias = flow.alias))]
+    async fn create_authentication_flow(
+        &self,
+        flow: &AuthenticationFlowRepresentation,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating authentication flow");
+        let response = api_client
+            .post_realm_authentication_flows()
+            .realm(&self.config.realm)
+            .body(flow.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::AuthenticationFlow),
+                )
+            })?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_authentication_flow(&self, flow_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting authentication flow");
+        api_client
+            .delete_realm_authentication_flows_id()
+            .realm(&self.config.realm)
+            .id(flow_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authentication_flow_executions(
+        &self,
+        flow_alias: &str,
+    ) -> Result<Vec<AuthenticationExecutionInfoRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying authentication flow executions");
+        let response = api_client
+            .get_realm_authentication_flows_flow_alias_executions()
+            .realm(&self.config.realm)
+            .flow_alias(flow_alias)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn copy_authentication_flow(&self, flow_alias: &str, new_name: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("copying authentication flow");
+        api_client
+            .post_realm_authentication_flows_flow_alias_copy()
+            .realm(&self.config.realm)
+            .flow_alias(flow_alias)
+            .body(HashMap::from([("newName".into(), new_name.into())]))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::AuthenticationFlow),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_authentication_execution(
+        &self,
+        flow_alias: &str,
+        provider: &str,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let mut body = serde_json::Map::new();
+        body.insert("provider".into(), provider.into());
+
+        tracing::debug!("adding execution to authentication flow");
+        let response = api_client
+            .post_realm_authentication_flows_flow_alias_executions_execution()
+            .realm(&self.config.realm)
+            .flow_alias(flow_alias)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_authentication_subflow(
+        &self,
+        flow_alias: &str,
+        alias: &str,
+        flow_type: &FlowType,
+        description: Option<&str>,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let mut body = serde_json::Map::new();
+        body.insert("alias".into(), alias.into());
+        body.insert("description".into(), description.unwrap_or_default().into());
+        match flow_type {
+            FlowType::Basic => {
+                body.insert("type".into(), "basic-flow".into());
+            }
+            FlowType::Form { provider } => {
+                body.insert("type".into(), "form-flow".into());
+                body.insert("provider".into(), provider.as_str().into());
+            }
+        }
+
+        tracing::debug!("adding sub-flow to authentication flow");
+        let response = api_client
+            .post_realm_authentication_flows_flow_alias_executions_flow()
+            .realm(&self.config.realm)
+            .flow_alias(flow_alias)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(flow_not_found)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::AuthenticationFlow),
+                )
+            })?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn raise_authentication_execution_priority(&self, execution_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("raising execution priority");
+        api_client
+            .post_realm_authentication_executions_execution_id_raise_priority()
+            .realm(&self.config.realm)
+            .execution_id(execution_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(execution_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn lower_authentication_execution_priority(&self, execution_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("lowering execution priority");
+        api_client
+            .post_realm_authentication_executions_execution_id_lower_priority()
+            .realm(&self.config.realm)
+            .execution_id(execution_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(execution_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn set_authentication_execution_requirement(
+        &self,
+        flow_alias: &str,
+        execution_id: &str,
+        requirement: ExecutionRequirement,
+    ) -> Result<()> {
+        // keycloak also applies the priority of the given execution, so send the current one
+        let mut execution = self
+            .authentication_flow_executions(flow_alias)
+            .await?
+            .into_iter()
+            .find(|execution| execution.id.as_deref() == Some(execution_id))
+            .ok_or_else(|| {
+                Error::new_kind(ErrorKind::NotFound(ResourceType::AuthenticationExecution))
+            })?;
+        execution.requirement = Some(requirement.as_str().into());
+
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating execution requirement");
+        api_client
+            .put_realm_authentication_flows_flow_alias_executions()
+            .realm(&self.config.realm)
+            .flow_alias(flow_alias)
+            .body(execution)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(execution_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn authenticator_config(
+        &self,
+        config_id: &str,
+    ) -> Result<AuthenticatorConfigRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying authenticator config");
+        let response = api_client
+            .get_realm_authentication_config_id()
+            .realm(&self.config.realm)
+            .id(config_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(config_not_found)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, config), fields(alias = config.alias))]
+    async fn create_authenticator_config(
+        &self,
+        execution_id: &str,
+        config: &AuthenticatorConfigRepresentation,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating authenticator config");
+        let response = api_client
+            .post_realm_authentication_executions_execution_id_config()
+            .realm(&self.config.realm)
+            .execution_id(execution_id)
+            .body(config.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(execution_not_found)?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self, config))]
+    async fn update_authenticator_config(
+        &self,
+        config_id: &str,
+        config: &AuthenticatorConfigRepresentation,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating authenticator config");
+        api_client
+            .put_realm_authentication_config_id()
+            .realm(&self.config.realm)
+            .id(config_id)
+            .body(config.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(config_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_authenticator_config(&self, config_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting authenticator config");
+        api_client
+            .delete_realm_authentication_config_id()
+            .realm(&self.config.realm)
+            .id(config_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(config_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn required_actions(&self) -> Result<Vec<RequiredActionProviderRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying required actions");
+        let response = api_client
+            .get_realm_authentication_required_actions()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn required_action(&self, alias: &str) -> Result<RequiredActionProviderRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying required action");
+        let response = api_client
+            .get_realm_authentication_required_actions_alias()
+            .realm(&self.config.realm)
+            .alias(alias)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(required_action_not_found)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn unregistered_required_actions(&self) -> Result<Vec<HashMap<String, String>>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying unregistered required actions");
+        let response = api_client
+            .get_realm_authentication_unregistered_required_actions()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn register_required_action(&self, provider_id: &str, name: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("registering required action");
+        api_client
+            .post_realm_authentication_register_required_action()
+            .realm(&self.config.realm)
+            .body(HashMap::from([
+                ("providerId".into(), provider_id.into()),
+                ("name".into(), name.into()),
+            ]))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::RequiredAction),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, action))]
+    async fn update_required_action(
+        &self,
+        alias: &str,
+        action: &RequiredActionProviderRepresentation,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating required action");
+        api_client
+            .put_realm_authentication_required_actions_alias()
+            .realm(&self.config.realm)
+            .alias(alias)
+            .body(action.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(required_action_not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, modify))]
+    async fn modify_required_action<F>(
+        &self,
+        alias: &str,
+        modify: F,
+    ) -> Result<RequiredActionProviderRepresentation>
+    where
+        F: FnOnce(&mut RequiredActionProviderRepresentation) + Send,
+    {
+        let mut action = self.required_action(alias).await?;
+        modify(&mut action);
+        self.update_required_action(alias, &action).await?;
+        Ok(action)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_required_action(&self, alias: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting required action");
+        api_client
+            .delete_realm_authentication_required_actions_alias()
+            .realm(&self.config.realm)
+            .alias(alias)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(required_action_not_found)?;
+        Ok(())
+    }
+}
+
+fn required_action_not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::RequiredAction),
+    )
+}
+
+fn config_not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::AuthenticatorConfig),
+    )
+}
+
+fn execution_not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::AuthenticationExecution),
+    )
+}
+
+fn flow_not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::AuthenticationFlow),
+    )
+}
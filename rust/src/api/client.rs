@@ -1,16 +1,89 @@
-use std::future::Future;
+use std::{collections::HashMap, future::Future};
+
+use reqwest::StatusCode;
 
 use crate::{
-    rest::types::{
-        AbstractPolicyRepresentation, ClientRepresentation, ClientScopeRepresentation,
-        PolicyRepresentation, ProtocolMapperRepresentation, ResourceRepresentation,
-        ResourceServerRepresentation, RolePolicyRepresentation, ScopeRepresentation,
+    error::ResourceType,
+    rest::{
+        types::{
+            AbstractPolicyRepresentation, ClientRepresentation, ClientScopeRepresentation,
+            CredentialRepresentation, GlobalRequestResult, ManagementPermissionReference,
+            PolicyRepresentation, ProtocolMapperRepresentation, ResourceRepresentation,
+            ResourceServerRepresentation, RolePolicyRepresentation, ScopeRepresentation,
+            TypedPermission, TypedPolicy, UserRepresentation, UserSessionRepresentation,
+        },
+        ClientSecret,
     },
+    util::RequestBuilderExt,
     Error, ErrorKind,
 };
 
+use super::{Ensured, KeycloakRealmExt};
+
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// options for [`ensure_client`](KeycloakClientExt::ensure_client)
+#[derive(Debug, Clone, Default)]
+pub struct EnsureClientOptions {
+    /// make the protocol mappers of an existing client match the given ones (by name), removing
+    /// all other mappers
+    pub protocol_mappers: bool,
+    /// make the default and optional client scopes of an existing client match the given ones
+    pub client_scopes: bool,
+}
+
+/// changes made by [`ensure_client`](KeycloakClientExt::ensure_client)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientChanges {
+    /// whether the client was created or updated, including its uuid
+    pub ensured: Ensured,
+    /// changed fields of an existing client (`attributes.<key>` for attributes)
+    pub fields: Vec<String>,
+    /// names of the added protocol mappers
+    pub added_protocol_mappers: Vec<String>,
+    /// names of the updated protocol mappers
+    pub updated_protocol_mappers: Vec<String>,
+    /// names of the removed protocol mappers
+    pub removed_protocol_mappers: Vec<String>,
+    /// names of the client scopes added to the default client scopes
+    pub added_default_scopes: Vec<String>,
+    /// names of the client scopes removed from the default client scopes
+    pub removed_default_scopes: Vec<String>,
+    /// names of the client scopes added to the optional client scopes
+    pub added_optional_scopes: Vec<String>,
+    /// names of the client scopes removed from the optional client scopes
+    pub removed_optional_scopes: Vec<String>,
+}
+
+impl ClientChanges {
+    fn new(ensured: Ensured) -> Self {
+        Self {
+            ensured,
+            fields: Vec::new(),
+            added_protocol_mappers: Vec::new(),
+            updated_protocol_mappers: Vec::new(),
+            removed_protocol_mappers: Vec::new(),
+            added_default_scopes: Vec::new(),
+            removed_default_scopes: Vec::new(),
+            added_optional_scopes: Vec::new(),
+            removed_optional_scopes: Vec::new(),
+        }
+    }
+
+    /// whether the client already existed and nothing had to be changed
+    pub fn is_empty(&self) -> bool {
+        !self.ensured.created()
+            && self.fields.is_empty()
+            && self.added_protocol_mappers.is_empty()
+            && self.updated_protocol_mappers.is_empty()
+            && self.removed_protocol_mappers.is_empty()
+            && self.added_default_scopes.is_empty()
+            && self.removed_default_scopes.is_empty()
+            && self.added_optional_scopes.is_empty()
+            && self.removed_optional_scopes.is_empty()
+    }
+}
+
 /// client-related methods of the keycloak api
 pub trait KeycloakClientExt {
     /// get all clients in the realm
@@ -30,6 +103,74 @@ pub trait KeycloakClientExt {
         client_uuid: &str,
     ) -> impl Future<Output = Result<ClientRepresentation>> + Send;
 
+    /// create a new client, returning its uuid
+    ///
+    /// fails with [`ErrorKind::Conflict`] if a client with the same client id already exists
+    fn create_client(
+        &self,
+        client: &ClientRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// update an existing client given its uuid
+    ///
+    /// keycloak replaces the whole client with the given representation, so this should usually
+    /// contain all fields of the client
+    fn update_client(
+        &self,
+        client_uuid: &str,
+        client: &ClientRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// create a client or update it if a client with the same client id already exists
+    ///
+    /// existing clients are only updated selectively: fields unset in `client` (as well as empty
+    /// lists and maps) keep their current values, and attributes are merged with the existing
+    /// ones. protocol mappers and client scopes of existing clients are left alone unless enabled
+    /// in `options`.
+    fn ensure_client(
+        &self,
+        client: &ClientRepresentation,
+        options: &EnsureClientOptions,
+    ) -> impl Future<Output = Result<ClientChanges>> + Send;
+
+    /// delete a client given its uuid
+    fn delete_client(&self, client_uuid: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the secret of a confidential client
+    fn client_secret(&self, client_uuid: &str)
+        -> impl Future<Output = Result<ClientSecret>> + Send;
+
+    /// generate a new secret for a confidential client, returning the new secret
+    ///
+    /// the old secret stops working immediately unless secret rotation is enabled for the client
+    fn regenerate_client_secret(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<ClientSecret>> + Send;
+
+    /// get the previous secret of a client during secret rotation
+    ///
+    /// returns `None` if the client has no rotated secret (anymore)
+    fn client_rotated_secret(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Option<ClientSecret>>> + Send;
+
+    /// invalidate the previous secret of a client before the end of the rotation period
+    fn invalidate_client_rotated_secret(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the user representing the service account of a client
+    ///
+    /// roles granted to this user are available to the client in the client credentials grant.
+    /// fails if service accounts are not enabled for the client.
+    fn client_service_account_user(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<UserRepresentation>> + Send;
+
     /// get the default client scopes of a client
     ///
     /// this only contains id/name for each client scope. to get the full configuration of these
@@ -48,18 +189,81 @@ pub trait KeycloakClientExt {
         client_uuid: &str,
     ) -> impl Future<Output = Result<Vec<ClientScopeRepresentation>>> + Send;
 
+    /// add a client scope to the default client scopes of a client
+    ///
+    /// default client scopes are always applied when the client requests a token
+    fn client_add_default_scope(
+        &self,
+        client_uuid: &str,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove a client scope from the default client scopes of a client
+    fn client_remove_default_scope(
+        &self,
+        client_uuid: &str,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// add a client scope to the optional client scopes of a client
+    ///
+    /// optional client scopes are only applied if the client requests them using the `scope`
+    /// parameter
+    fn client_add_optional_scope(
+        &self,
+        client_uuid: &str,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove a client scope from the optional client scopes of a client
+    fn client_remove_optional_scope(
+        &self,
+        client_uuid: &str,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get a client's authorization service settings
     fn client_authz_resource_server(
         &self,
         client_uuid: &str,
     ) -> impl Future<Output = Result<ResourceServerRepresentation>> + Send;
 
+    /// export the complete authorization settings of a client
+    ///
+    /// this includes all resources, scopes, policies and permissions, referencing each other by
+    /// name. the result can be imported into another client using
+    /// [`client_authz_import`](KeycloakClientExt::client_authz_import)
+    fn client_authz_export(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<ResourceServerRepresentation>> + Send;
+
+    /// import authorization settings exported using
+    /// [`client_authz_export`](KeycloakClientExt::client_authz_export) into a client
+    ///
+    /// existing resources, scopes, policies and permissions with the same names are updated,
+    /// others are left untouched
+    fn client_authz_import(
+        &self,
+        client_uuid: &str,
+        settings: &ResourceServerRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get all authorization resources of a client
     fn client_authz_resources(
         &self,
         client_uuid: &str,
     ) -> impl Future<Output = Result<Vec<ResourceRepresentation>>> + Send;
 
+    /// get a single authorization resource of a client given its name
+    ///
+    /// the name is matched exactly. this will return an error if not exactly one resource is found
+    fn client_authz_resource_by_name(
+        &self,
+        client_uuid: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<ResourceRepresentation>> + Send;
+
     /// get the permissions associated with a client's authorization resource
     fn client_authz_resource_permissions(
         &self,
@@ -80,12 +284,51 @@ pub trait KeycloakClientExt {
         client_uuid: &str,
     ) -> impl Future<Output = Result<Vec<ScopeRepresentation>>> + Send;
 
+    /// get a single authorization scope of a client given its name
+    ///
+    /// the name is matched exactly. this will return an error if not exactly one scope is found
+    fn client_authz_scope_by_name(
+        &self,
+        client_uuid: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<ScopeRepresentation>> + Send;
+
     /// get all authorization permissions of a client
     fn client_authz_permissions(
         &self,
         client_uuid: &str,
     ) -> impl Future<Output = Result<Vec<AbstractPolicyRepresentation>>> + Send;
 
+    /// create a client authorization permission, returning its id
+    ///
+    /// the permission type is determined by the representation, e.g.
+    /// [`ScopePermissionRepresentation`](crate::rest::types::ScopePermissionRepresentation)
+    #[cfg(feature = "unstable")]
+    fn create_client_authz_permission<P: TypedPermission + Sync>(
+        &self,
+        client_uuid: &str,
+        permission: &P,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// update a client authorization permission
+    ///
+    /// the permission's `id` must be set. this replaces the whole permission with the given values,
+    /// including the associated policies, resources and scopes
+    #[cfg(feature = "unstable")]
+    fn update_client_authz_permission<P: TypedPermission + Sync>(
+        &self,
+        client_uuid: &str,
+        permission: &P,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a client authorization permission of any type
+    #[cfg(feature = "unstable")]
+    fn delete_client_authz_permission(
+        &self,
+        client_uuid: &str,
+        permission_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get all authorization policies of a client
     fn client_authz_policies(
         &self,
@@ -100,6 +343,38 @@ pub trait KeycloakClientExt {
         policy_id: &str,
     ) -> impl Future<Output = Result<PolicyRepresentation>> + Send;
 
+    /// get a client authorization policy given its name
+    ///
+    /// the name is matched exactly. the policy is converted to the requested representation, e.g.
+    /// [`RolePolicyRepresentation`], failing with [`ErrorKind::WrongType`] if it has another type.
+    /// use [`Policy`](crate::rest::types::Policy) to accept policies of any type
+    #[cfg(feature = "unstable")]
+    fn client_authz_policy_by_name<P: TryFrom<PolicyRepresentation, Error = Error> + Send>(
+        &self,
+        client_uuid: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<P>> + Send;
+
+    /// create a client authorization policy, returning its id
+    ///
+    /// the policy type is determined by the representation, e.g. [`RolePolicyRepresentation`]
+    #[cfg(feature = "unstable")]
+    fn create_client_authz_policy<P: TypedPolicy + Sync>(
+        &self,
+        client_uuid: &str,
+        policy: &P,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// update a client authorization policy
+    ///
+    /// the policy's `id` must be set. this replaces the whole policy with the given values
+    #[cfg(feature = "unstable")]
+    fn update_client_authz_policy<P: TypedPolicy + Sync>(
+        &self,
+        client_uuid: &str,
+        policy: &P,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// update a client authorization policy of type role
     #[cfg(feature = "unstable")]
     fn update_client_authz_role_policy(
@@ -108,6 +383,14 @@ pub trait KeycloakClientExt {
         policy: &RolePolicyRepresentation,
     ) -> impl Future<Output = Result<()>> + Send;
 
+    /// delete a client authorization policy of any type
+    #[cfg(feature = "unstable")]
+    fn delete_client_authz_policy(
+        &self,
+        client_uuid: &str,
+        policy_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get the authorization policies associated with a client authorization policy
     #[cfg(feature = "unstable")]
     fn client_authz_policy_associated_policies(
@@ -140,6 +423,33 @@ pub trait KeycloakClientExt {
         policy_id: &str,
     ) -> impl Future<Output = Result<Vec<ScopeRepresentation>>> + Send;
 
+    /// get the protocol mappers configured in a client's dedicated client scope
+    fn client_protocol_mappers(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<ProtocolMapperRepresentation>>> + Send;
+
+    /// add a protocol mapper to a client's dedicated client scope, returning its id
+    fn add_client_protocol_mapper(
+        &self,
+        client_uuid: &str,
+        mapper: &ProtocolMapperRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// add multiple protocol mappers to a client's dedicated client scope in a single request
+    fn add_client_protocol_mappers(
+        &self,
+        client_uuid: &str,
+        mappers: &[ProtocolMapperRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a protocol mapper from a client's dedicated client scope
+    fn delete_client_protocol_mapper(
+        &self,
+        client_uuid: &str,
+        mapper_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// update a dedicated protocol mapper configured in a client's dedicated client scope
     ///
     /// the `id` must reference an existing protocol mapper in the given [`ProtocolMapperRepresentation`]
@@ -150,6 +460,85 @@ pub trait KeycloakClientExt {
         client_id: &str,
         mapper: &ProtocolMapperRepresentation,
     ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the number of active user sessions of a client
+    fn client_session_count(&self, client_uuid: &str) -> impl Future<Output = Result<u64>> + Send;
+
+    /// get a page of the active user sessions of a client
+    fn client_user_sessions(
+        &self,
+        client_uuid: &str,
+        first: Option<i32>,
+        max: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<UserSessionRepresentation>>> + Send;
+
+    /// get the number of offline sessions of a client
+    fn client_offline_session_count(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// get all offline sessions of a client
+    fn client_offline_sessions(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<UserSessionRepresentation>>> + Send;
+
+    /// manually register a cluster node of a client using a legacy adapter
+    ///
+    /// `node` is the host name of the node, the registered nodes are listed in the client's
+    /// `registered_nodes`
+    fn register_client_node(
+        &self,
+        client_uuid: &str,
+        node: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// unregister a cluster node of a client
+    fn unregister_client_node(
+        &self,
+        client_uuid: &str,
+        node: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// check which registered cluster nodes of a client are reachable
+    ///
+    /// keycloak sends a request to the admin url of every registered node
+    fn client_test_nodes_available(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<GlobalRequestResult>> + Send;
+
+    /// push the not-before policy of a client to its admin url
+    fn client_push_revocation(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<GlobalRequestResult>> + Send;
+
+    /// revoke all tokens of a client issued before `not_before` (seconds since the unix epoch)
+    ///
+    /// `0` removes the policy. the current policy is the `not_before` of
+    /// [`client_by_uuid`](Self::client_by_uuid). the realm's policy applies in addition to this.
+    fn set_client_not_before(
+        &self,
+        client_uuid: &str,
+        not_before: i32,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the fine-grained admin permissions of a client
+    fn client_management_permissions(
+        &self,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<ManagementPermissionReference>> + Send;
+
+    /// enable or disable the fine-grained admin permissions of a client
+    ///
+    /// returns the permissions including the ids of the created authorization scope permissions
+    fn update_client_management_permissions(
+        &self,
+        client_uuid: &str,
+        permissions: &ManagementPermissionReference,
+    ) -> impl Future<Output = Result<ManagementPermissionReference>> + Send;
 }
 
 impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate::Keycloak<A> {
@@ -161,15 +550,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         tracing::debug!("querying all clients in realm");
         let clients = paginate_api!(|first, max| {
             api_client
-                .get_realm_clients(
-                    &self.config.realm,
-                    None,
-                    Some(first),
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                )
+                .get_realm_clients()
+                .realm(&self.config.realm)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -185,15 +570,12 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         tracing::debug!("querying client in realm by client id");
         let mut clients = paginate_api!(|first, max| {
             api_client
-                .get_realm_clients(
-                    &self.config.realm,
-                    Some(client_id),
-                    Some(first),
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                )
+                .get_realm_clients()
+                .realm(&self.config.realm)
+                .client_id(client_id)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -219,160 +601,560 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client in realm by uuid");
         let response = api_client
-            .get_realm_client(&self.config.realm, client_uuid)
+            .get_realm_client()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 
-    #[tracing::instrument(skip(self))]
-    async fn client_default_scopes(
-        &self,
-        client_uuid: &str,
-    ) -> Result<Vec<ClientScopeRepresentation>> {
+    #[tracing::instrument(skip(self, client), fields(client_id = client.client_id))]
+    async fn create_client(&self, client: &ClientRepresentation) -> Result<String> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying default client scopes");
+        tracing::debug!("creating client");
         let response = api_client
-            .get_realm_client_default_client_scopes(&self.config.realm, client_uuid)
+            .post_realm_clients()
+            .realm(&self.config.realm)
+            .body(client.clone())
+            .send()
             .await
-            .map_err(crate::error::progenitor)?
-            .into_inner();
-        Ok(response)
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Client),
+                )
+            })?;
+        super::id_from_location(&response)
     }
 
-    #[tracing::instrument(skip(self))]
-    async fn client_optional_scopes(
+    #[tracing::instrument(skip(self, client), fields(client_id = client.client_id))]
+    async fn ensure_client(
         &self,
-        client_uuid: &str,
-    ) -> Result<Vec<ClientScopeRepresentation>> {
+        client: &ClientRepresentation,
+        options: &EnsureClientOptions,
+    ) -> Result<ClientChanges> {
+        let client_id = client
+            .client_id
+            .as_deref()
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingField("clientId".into())))?;
+        let existing = match self.client_by_id(client_id).await {
+            Ok(existing) => existing,
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound(_)) => {
+                match self.create_client(client).await {
+                    Ok(id) => return Ok(ClientChanges::new(Ensured::Created(id))),
+                    // created concurrently, update that client instead
+                    Err(e) if matches!(e.kind(), ErrorKind::Conflict(_)) => {
+                        tracing::debug!("client was created concurrently");
+                        self.client_by_id(client_id).await?
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        let client_uuid = existing
+            .id
+            .clone()
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+        let mut changes = ClientChanges::new(Ensured::Updated(client_uuid.clone()));
+
+        let (merged, fields) = merge_client(&existing, client)?;
+        if !fields.is_empty() {
+            self.update_client(&client_uuid, &merged).await?;
+            changes.fields = fields;
+        }
+
+        if options.protocol_mappers {
+            let current = self.client_protocol_mappers(&client_uuid).await?;
+            let (added, updated, removed) =
+                protocol_mapper_changes(&current, &client.protocol_mappers);
+            for mapper in &removed {
+                let mapper_id = mapper
+                    .id
+                    .as_deref()
+                    .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+                self.delete_client_protocol_mapper(&client_uuid, mapper_id)
+                    .await?;
+            }
+            for mapper in &updated {
+                self.update_client_protocol_mapper(&client_uuid, mapper)
+                    .await?;
+            }
+            if !added.is_empty() {
+                self.add_client_protocol_mappers(&client_uuid, &added)
+                    .await?;
+            }
+            let names = |mappers: Vec<ProtocolMapperRepresentation>| {
+                mappers.into_iter().filter_map(|m| m.name).collect()
+            };
+            changes.added_protocol_mappers = names(added);
+            changes.updated_protocol_mappers = names(updated);
+            changes.removed_protocol_mappers = names(removed);
+        }
+
+        if options.client_scopes {
+            let current_default = self.client_default_scopes(&client_uuid).await?;
+            let current_optional = self.client_optional_scopes(&client_uuid).await?;
+            let (add_default, remove_default) =
+                scope_changes(&current_default, &client.default_client_scopes);
+            let (add_optional, remove_optional) =
+                scope_changes(&current_optional, &client.optional_client_scopes);
+
+            // remove first, so scopes can move between default and optional
+            for scope in &remove_default {
+                let scope_id = scope_id(scope)?;
+                self.client_remove_default_scope(&client_uuid, scope_id)
+                    .await?;
+            }
+            for scope in &remove_optional {
+                let scope_id = scope_id(scope)?;
+                self.client_remove_optional_scope(&client_uuid, scope_id)
+                    .await?;
+            }
+            if !add_default.is_empty() || !add_optional.is_empty() {
+                let scopes = self.client_scopes().await?;
+                let scope_id_by_name = |name: &str| {
+                    scopes
+                        .iter()
+                        .find(|scope| scope.name.as_deref() == Some(name))
+                        .and_then(|scope| scope.id.clone())
+                        .ok_or_else(|| {
+                            Error::new_kind(ErrorKind::NotFound(ResourceType::ClientScope))
+                        })
+                };
+                for name in &add_default {
+                    self.client_add_default_scope(&client_uuid, &scope_id_by_name(name)?)
+                        .await?;
+                }
+                for name in &add_optional {
+                    self.client_add_optional_scope(&client_uuid, &scope_id_by_name(name)?)
+                        .await?;
+                }
+            }
+            let names = |scopes: Vec<ClientScopeRepresentation>| {
+                scopes.into_iter().filter_map(|s| s.name).collect()
+            };
+            changes.added_default_scopes = add_default;
+            changes.removed_default_scopes = names(remove_default);
+            changes.added_optional_scopes = add_optional;
+            changes.removed_optional_scopes = names(remove_optional);
+        }
+
+        Ok(changes)
+    }
+
+    #[tracing::instrument(skip(self, client))]
+    async fn update_client(&self, client_uuid: &str, client: &ClientRepresentation) -> Result<()> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying optional client scopes");
-        let response = api_client
-            .get_realm_client_optional_client_scopes(&self.config.realm, client_uuid)
+        tracing::debug!("updating client");
+        api_client
+            .put_realm_client()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(client.clone())
+            .send()
             .await
-            .map_err(crate::error::progenitor)?
-            .into_inner();
-        Ok(response)
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Client),
+                )
+            })?;
+        Ok(())
     }
 
     #[tracing::instrument(skip(self))]
-    async fn client_authz_resource_server(
-        &self,
-        client_uuid: &str,
-    ) -> Result<ResourceServerRepresentation> {
+    async fn delete_client(&self, client_uuid: &str) -> Result<()> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying client authz resource server");
-        let response = api_client
-            .get_realm_client_authz_resource_server(&self.config.realm, client_uuid)
+        tracing::debug!("deleting client");
+        api_client
+            .delete_realm_client()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::Client),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_secret(&self, client_uuid: &str) -> Result<ClientSecret> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client secret");
+        let credential = api_client
+            .get_realm_client_client_secret()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
-        Ok(response)
+        secret_from_credential(credential)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn client_authz_resources(
-        &self,
-        client_uuid: &str,
-    ) -> Result<Vec<ResourceRepresentation>> {
+    async fn regenerate_client_secret(&self, client_uuid: &str) -> Result<ClientSecret> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying client authz resources");
-        let response = paginate_api!(|first, max| {
-            api_client
-                .get_realm_client_authz_resource_server_resource(
-                    &self.config.realm,
-                    client_uuid,
-                    None,
-                    None,
-                    None,
-                    Some(first),
-                    None,
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-                .await
-                .map_err(crate::error::progenitor)?
-                .into_inner()
-        });
-        Ok(response)
+        tracing::debug!("regenerating client secret");
+        let credential = api_client
+            .post_realm_client_client_secret()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        secret_from_credential(credential)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn client_authz_resource_permissions(
-        &self,
-        client_uuid: &str,
-        resource_id: &str,
-    ) -> Result<Vec<PolicyRepresentation>> {
+    async fn client_rotated_secret(&self, client_uuid: &str) -> Result<Option<ClientSecret>> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying client authz resource permissions");
-        let response = paginate_api!(|first, max| {
-            api_client
-                .get_realm_client_authz_resource_server_resource_by_id_permissions(
-                    &self.config.realm,
-                    client_uuid,
-                    resource_id,
-                    None,
-                    None,
-                    None,
-                    Some(first),
-                    None,
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-                .await
-                .map_err(crate::error::progenitor)?
-                .into_inner()
-        });
-        Ok(response)
+        tracing::debug!("querying rotated client secret");
+        let result = api_client
+            .get_realm_client_client_secret_rotated()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await;
+        let credential = match result {
+            Ok(response) => response.into_inner(),
+            // keycloak responds with `404 Not Found` if there is no rotated secret
+            Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => return Ok(None),
+            Err(e) => return Err(crate::error::progenitor(e)),
+        };
+        secret_from_credential(credential).map(Some)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn client_authz_resource_scopes(
-        &self,
-        client_uuid: &str,
-        resource_id: &str,
-    ) -> Result<Vec<ScopeRepresentation>> {
+    async fn invalidate_client_rotated_secret(&self, client_uuid: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("invalidating rotated client secret");
+        api_client
+            .delete_realm_client_client_secret_rotated()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_service_account_user(&self, client_uuid: &str) -> Result<UserRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying service account user");
+        let user = api_client
+            .get_realm_client_service_account_user()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(user)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_default_scopes(
+        &self,
+        client_uuid: &str,
+    ) -> Result<Vec<ClientScopeRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying default client scopes");
+        let response = api_client
+            .get_realm_client_default_client_scopes()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_optional_scopes(
+        &self,
+        client_uuid: &str,
+    ) -> Result<Vec<ClientScopeRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying optional client scopes");
+        let response = api_client
+            .get_realm_client_optional_client_scopes()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_add_default_scope(&self, client_uuid: &str, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding default client scope");
+        api_client
+            .put_realm_client_default_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_remove_default_scope(&self, client_uuid: &str, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing default client scope");
+        api_client
+            .delete_realm_client_default_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_add_optional_scope(&self, client_uuid: &str, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding optional client scope");
+        api_client
+            .put_realm_client_optional_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_remove_optional_scope(&self, client_uuid: &str, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing optional client scope");
+        api_client
+            .delete_realm_client_optional_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_resource_server(
+        &self,
+        client_uuid: &str,
+    ) -> Result<ResourceServerRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client authz resource server");
+        let response = api_client
+            .get_realm_client_authz_resource_server()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_export(&self, client_uuid: &str) -> Result<ResourceServerRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("exporting client authz settings");
+        let settings = api_client
+            .get_realm_client_authz_resource_server_settings()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(settings)
+    }
+
+    #[tracing::instrument(skip(self, settings))]
+    async fn client_authz_import(
+        &self,
+        client_uuid: &str,
+        settings: &ResourceServerRepresentation,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("importing client authz settings");
+        api_client
+            .post_realm_client_authz_resource_server_import()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(settings.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_resources(
+        &self,
+        client_uuid: &str,
+    ) -> Result<Vec<ResourceRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client authz resources");
+        let response = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_authz_resource_server_resource()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_resource_by_name(
+        &self,
+        client_uuid: &str,
+        name: &str,
+    ) -> Result<ResourceRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client authz resource by name");
+        let mut resources = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_authz_resource_server_resource()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .name(name)
+                .exact_name(true)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        if resources.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotFound(
+                ResourceType::AuthzResource,
+            )));
+        }
+        let resource = resources.pop().unwrap();
+        if !resources.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotUnique(
+                ResourceType::AuthzResource,
+            )));
+        }
+        Ok(resource)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_resource_permissions(
+        &self,
+        client_uuid: &str,
+        resource_id: &str,
+    ) -> Result<Vec<PolicyRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client authz resource permissions");
+        let response = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_authz_resource_server_resource_by_id_permissions()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .resource_id(resource_id)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_resource_scopes(
+        &self,
+        client_uuid: &str,
+        resource_id: &str,
+    ) -> Result<Vec<ScopeRepresentation>> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
         tracing::debug!("querying client authz resource scopes");
         let response = paginate_api!(|first, max| {
             api_client
-                .get_realm_client_authz_resource_server_resource_by_id_scopes(
-                    &self.config.realm,
-                    client_uuid,
-                    resource_id,
-                    None,
-                    None,
-                    None,
-                    Some(first),
-                    None,
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
+                .get_realm_client_authz_resource_server_resource_by_id_scopes()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .resource_id(resource_id)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -388,14 +1170,12 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         tracing::debug!("querying client authz scopes");
         let response = paginate_api!(|first, max| {
             api_client
-                .get_realm_client_authz_resource_server_scope(
-                    &self.config.realm,
-                    client_uuid,
-                    Some(first),
-                    Some(max),
-                    None,
-                    None,
-                )
+                .get_realm_client_authz_resource_server_scope()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -403,6 +1183,48 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn client_authz_scope_by_name(
+        &self,
+        client_uuid: &str,
+        name: &str,
+    ) -> Result<ScopeRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client authz scope by name");
+        let scopes = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_authz_resource_server_scope()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .name(name)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        // the name filter matches substrings, so filter the exact matches
+        let mut scopes: Vec<_> = scopes
+            .into_iter()
+            .filter(|scope| scope.name.as_deref() == Some(name))
+            .collect();
+        if scopes.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotFound(
+                ResourceType::AuthzScope,
+            )));
+        }
+        let scope = scopes.pop().unwrap();
+        if !scopes.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotUnique(
+                ResourceType::AuthzScope,
+            )));
+        }
+        Ok(scope)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn client_authz_permissions(
         &self,
@@ -414,20 +1236,12 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         tracing::debug!("querying client authz scopes");
         let response = paginate_api!(|first, max| {
             api_client
-                .get_realm_client_authz_resource_server_permission(
-                    &self.config.realm,
-                    client_uuid,
-                    None,
-                    Some(first),
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
+                .get_realm_client_authz_resource_server_permission()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -435,6 +1249,89 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         Ok(response)
     }
 
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self, permission), fields(name = permission.policy().name))]
+    async fn create_client_authz_permission<P: TypedPermission + Sync>(
+        &self,
+        client_uuid: &str,
+        permission: &P,
+    ) -> Result<String> {
+        let body = crate::rest::types::typed_policy_body(permission)?;
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating authz permission");
+        let created = api_client
+            .post_realm_client_authz_resource_server_permission_by_type()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_type(P::TYPE)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Policy),
+                )
+            })?
+            .into_inner();
+        created
+            .id
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self, permission), fields(name = permission.policy().name))]
+    async fn update_client_authz_permission<P: TypedPermission + Sync>(
+        &self,
+        client_uuid: &str,
+        permission: &P,
+    ) -> Result<()> {
+        let Some(permission_id) = permission.policy().id.as_ref() else {
+            return Err(Error::new_kind(ErrorKind::MissingId));
+        };
+        let body = crate::rest::types::typed_policy_body(permission)?;
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating authz permission");
+        api_client
+            .put_realm_client_authz_resource_server_permission_by_type_policy_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_type(P::TYPE)
+            .policy_id(permission_id)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self))]
+    async fn delete_client_authz_permission(
+        &self,
+        client_uuid: &str,
+        permission_id: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting authz permission");
+        api_client
+            .delete_realm_client_authz_resource_server_permission_by_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(permission_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     async fn client_authz_policies(
         &self,
@@ -446,20 +1343,12 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         tracing::debug!("querying client authz policies");
         let response = paginate_api!(|first, max| {
             api_client
-                .get_realm_client_authz_resource_server_policy(
-                    &self.config.realm,
-                    client_uuid,
-                    None,
-                    Some(first),
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
+                .get_realm_client_authz_resource_server_policy()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -479,12 +1368,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client authz policy");
         let response = api_client
-            .get_realm_client_authz_resource_server_policy_by_id(
-                &self.config.realm,
-                client_uuid,
-                policy_id,
-                None,
-            )
+            .get_realm_client_authz_resource_server_policy_by_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -493,32 +1381,136 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
     #[cfg(feature = "unstable")]
     #[tracing::instrument(skip(self))]
-    async fn update_client_authz_role_policy(
+    async fn client_authz_policy_by_name<P: TryFrom<PolicyRepresentation, Error = Error> + Send>(
         &self,
         client_uuid: &str,
-        policy: &RolePolicyRepresentation,
+        name: &str,
+    ) -> Result<P> {
+        let policy_id = {
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("querying client authz policy by name");
+            let policies = paginate_api!(|first, max| {
+                api_client
+                    .get_realm_client_authz_resource_server_policy()
+                    .realm(&self.config.realm)
+                    .client_uuid(client_uuid)
+                    .name(name)
+                    .first(first)
+                    .max(max)
+                    .send()
+                    .await
+                    .map_err(crate::error::progenitor)?
+                    .into_inner()
+            });
+            // the name filter matches substrings, so filter the exact matches
+            let mut ids: Vec<_> = policies
+                .into_iter()
+                .filter(|policy| policy.name.as_deref() == Some(name))
+                .filter_map(|policy| policy.id)
+                .collect();
+            if ids.is_empty() {
+                return Err(Error::new_kind(ErrorKind::NotFound(ResourceType::Policy)));
+            }
+            let id = ids.pop().unwrap();
+            if !ids.is_empty() {
+                return Err(Error::new_kind(ErrorKind::NotUnique(ResourceType::Policy)));
+            }
+            id
+        };
+
+        // the listed abstract representations have no config, so fetch the full policy
+        let policy = self.client_authz_policy(client_uuid, &policy_id).await?;
+        P::try_from(policy)
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self, policy), fields(name = policy.policy().name))]
+    async fn create_client_authz_policy<P: TypedPolicy + Sync>(
+        &self,
+        client_uuid: &str,
+        policy: &P,
+    ) -> Result<String> {
+        let body = crate::rest::types::typed_policy_body(policy)?;
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating authz policy");
+        let created = api_client
+            .post_realm_client_authz_resource_server_policy_by_type()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_type(P::TYPE)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Policy),
+                )
+            })?
+            .into_inner();
+        created
+            .id
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self, policy), fields(name = policy.policy().name))]
+    async fn update_client_authz_policy<P: TypedPolicy + Sync>(
+        &self,
+        client_uuid: &str,
+        policy: &P,
     ) -> Result<()> {
-        let Some(policy_id) = policy.policy.id.as_ref() else {
+        let Some(policy_id) = policy.policy().id.as_ref() else {
             return Err(Error::new_kind(ErrorKind::MissingId));
         };
+        let body = crate::rest::types::typed_policy_body(policy)?;
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
         tracing::debug!("updating authz policy");
-        let serde_json::Value::Object(map) = serde_json::to_value(policy).unwrap() else {
-            panic!();
-        };
         api_client
-            .put_realm_client_authz_resource_server_policy_by_type_policy_id(
-                &self.config.realm,
-                client_uuid,
-                "role",
-                policy_id,
-                &map,
-            )
+            .put_realm_client_authz_resource_server_policy_by_type_policy_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_type(P::TYPE)
+            .policy_id(policy_id)
+            .body(body)
+            .send()
             .await
             .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self))]
+    async fn update_client_authz_role_policy(
+        &self,
+        client_uuid: &str,
+        policy: &RolePolicyRepresentation,
+    ) -> Result<()> {
+        self.update_client_authz_policy(client_uuid, policy).await
+    }
+
+    #[cfg(feature = "unstable")]
+    #[tracing::instrument(skip(self))]
+    async fn delete_client_authz_policy(&self, client_uuid: &str, policy_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
 
+        tracing::debug!("deleting authz policy");
+        api_client
+            .delete_realm_client_authz_resource_server_policy_by_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
         Ok(())
     }
 
@@ -534,11 +1526,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client authz policy associated policies");
         let response = api_client
-            .get_realm_client_authz_resource_server_policy_by_id_associated_policies(
-                &self.config.realm,
-                client_uuid,
-                policy_id,
-            )
+            .get_realm_client_authz_resource_server_policy_by_id_associated_policies()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -557,11 +1549,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client authz policy dependent policies");
         let response = api_client
-            .get_realm_client_authz_resource_server_policy_by_id_dependent_policies(
-                &self.config.realm,
-                client_uuid,
-                policy_id,
-            )
+            .get_realm_client_authz_resource_server_policy_by_id_dependent_policies()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -580,11 +1572,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client authz policy resources");
         let response = api_client
-            .get_realm_client_authz_resource_server_policy_by_id_resources(
-                &self.config.realm,
-                client_uuid,
-                policy_id,
-            )
+            .get_realm_client_authz_resource_server_policy_by_id_resources()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -603,17 +1595,100 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
 
         tracing::debug!("querying client authz policy scopes");
         let response = api_client
-            .get_realm_client_authz_resource_server_policy_by_id_scopes(
-                &self.config.realm,
-                client_uuid,
-                policy_id,
-            )
+            .get_realm_client_authz_resource_server_policy_by_id_scopes()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .policy_id(policy_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn client_protocol_mappers(
+        &self,
+        client_uuid: &str,
+    ) -> Result<Vec<ProtocolMapperRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying protocol mappers");
+        let mappers = api_client
+            .get_realm_client_protocol_mappers_models()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(mappers)
+    }
+
+    #[tracing::instrument(skip(self, mapper), fields(name = mapper.name))]
+    async fn add_client_protocol_mapper(
+        &self,
+        client_uuid: &str,
+        mapper: &ProtocolMapperRepresentation,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding protocol mapper");
+        let response = api_client
+            .post_realm_client_protocol_mappers_models()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(mapper.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self, mappers), fields(count = mappers.len()))]
+    async fn add_client_protocol_mappers(
+        &self,
+        client_uuid: &str,
+        mappers: &[ProtocolMapperRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding protocol mappers");
+        api_client
+            .post_realm_client_protocol_mappers_add_models()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(mappers.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_client_protocol_mapper(
+        &self,
+        client_uuid: &str,
+        mapper_id: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting protocol mapper");
+        api_client
+            .delete_realm_client_protocol_mappers_models_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .id(mapper_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     async fn update_client_protocol_mapper(
         &self,
@@ -627,16 +1702,427 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakClientExt for crate
         let api_client = self.api_client.read().await;
 
         tracing::debug!("updating protocol mapper");
+        api_client
+            .put_realm_client_protocol_mappers_models_id()
+            .realm(&self.config.realm)
+            .client_uuid(client_id)
+            .id(protocol_mapper_id)
+            .body(mapper.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_session_count(&self, client_uuid: &str) -> Result<u64> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying session count of client");
+        let response = api_client
+            .get_realm_client_session_count()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        let Some(&count) = response.get("count") else {
+            return Err(Error::new_kind(ErrorKind::MissingField("count".into())));
+        };
+        super::count_from(count)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_user_sessions(
+        &self,
+        client_uuid: &str,
+        first: Option<i32>,
+        max: Option<i32>,
+    ) -> Result<Vec<UserSessionRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user sessions of client");
+        let sessions = api_client
+            .get_realm_client_user_sessions()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .set_opt(first, |r, v| r.first(v))
+            .set_opt(max, |r, v| r.max(v))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(sessions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_offline_session_count(&self, client_uuid: &str) -> Result<u64> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying offline session count of client");
+        let response = api_client
+            .get_realm_client_offline_session_count()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        let Some(&count) = response.get("count") else {
+            return Err(Error::new_kind(ErrorKind::MissingField("count".into())));
+        };
+        super::count_from(count)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_offline_sessions(
+        &self,
+        client_uuid: &str,
+    ) -> Result<Vec<UserSessionRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying offline sessions of client");
+        let sessions = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_offline_sessions()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(sessions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn register_client_node(&self, client_uuid: &str, node: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("registering cluster node");
+        api_client
+            .post_realm_client_nodes()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(HashMap::from([("node".to_string(), node.to_string())]))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn unregister_client_node(&self, client_uuid: &str, node: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("unregistering cluster node");
+        api_client
+            .delete_realm_client_nodes_node()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .node(node)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_test_nodes_available(&self, client_uuid: &str) -> Result<GlobalRequestResult> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("testing availability of cluster nodes");
+        let result = api_client
+            .get_realm_client_test_nodes_available()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_push_revocation(&self, client_uuid: &str) -> Result<GlobalRequestResult> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("pushing revocation policy of client");
+        let result = api_client
+            .post_realm_client_push_revocation()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::Client),
+                )
+            })?
+            .into_inner();
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn set_client_not_before(&self, client_uuid: &str, not_before: i32) -> Result<()> {
+        let mut client = self.client_by_uuid(client_uuid).await?;
+        client.not_before = Some(not_before);
+        self.update_client(client_uuid, &client).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_management_permissions(
+        &self,
+        client_uuid: &str,
+    ) -> Result<ManagementPermissionReference> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client management permissions");
+        let response = api_client
+            .get_realm_client_management_permissions()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update_client_management_permissions(
+        &self,
+        client_uuid: &str,
+        permissions: &ManagementPermissionReference,
+    ) -> Result<ManagementPermissionReference> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating client management permissions");
         let response = api_client
-            .put_realm_client_protocol_mappers_models_id(
-                &self.config.realm,
-                client_id,
-                &protocol_mapper_id,
-                &mapper,
-            )
+            .put_realm_client_management_permissions()
+            .realm(&self.config.realm)
+            .client_uuid(client_uuid)
+            .body(permissions.clone())
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 }
+
+/// apply the fields set in `desired` to `existing`, returning the result and the changed fields
+///
+/// protocol mappers and client scopes are managed separately and therefore skipped
+fn merge_client(
+    existing: &ClientRepresentation,
+    desired: &ClientRepresentation,
+) -> Result<(ClientRepresentation, Vec<String>)> {
+    const SKIPPED: [&str; 4] = [
+        "id",
+        "protocolMappers",
+        "defaultClientScopes",
+        "optionalClientScopes",
+    ];
+
+    let serde_json::Value::Object(mut merged) =
+        serde_json::to_value(existing).map_err(crate::error::deserialize)?
+    else {
+        return Err(Error::new_kind(ErrorKind::WrongType(
+            "object".into(),
+            "non-object".into(),
+        )));
+    };
+    let serde_json::Value::Object(desired) =
+        serde_json::to_value(desired).map_err(crate::error::deserialize)?
+    else {
+        return Err(Error::new_kind(ErrorKind::WrongType(
+            "object".into(),
+            "non-object".into(),
+        )));
+    };
+
+    let mut fields = Vec::new();
+    for (key, value) in desired {
+        if SKIPPED.contains(&key.as_str()) {
+            continue;
+        }
+        match (merged.get_mut(&key), value) {
+            // merge maps like the attributes instead of replacing them
+            (Some(serde_json::Value::Object(current)), serde_json::Value::Object(value)) => {
+                for (field, value) in value {
+                    if current.get(&field) != Some(&value) {
+                        fields.push(format!("{key}.{field}"));
+                        current.insert(field, value);
+                    }
+                }
+            }
+            (current, value) => {
+                if current.as_deref() != Some(&value) {
+                    fields.push(key.clone());
+                    merged.insert(key, value);
+                }
+            }
+        }
+    }
+
+    let merged = serde_json::from_value(serde_json::Value::Object(merged))
+        .map_err(crate::error::deserialize)?;
+    Ok((merged, fields))
+}
+
+/// compare protocol mappers by name, returning the mappers to add, update and remove
+///
+/// the mappers to update have the id of the existing mapper
+fn protocol_mapper_changes(
+    current: &[ProtocolMapperRepresentation],
+    desired: &[ProtocolMapperRepresentation],
+) -> (
+    Vec<ProtocolMapperRepresentation>,
+    Vec<ProtocolMapperRepresentation>,
+    Vec<ProtocolMapperRepresentation>,
+) {
+    let mut added = Vec::new();
+    let mut updated = Vec::new();
+    for mapper in desired {
+        match current.iter().find(|m| m.name == mapper.name) {
+            None => added.push(mapper.clone()),
+            Some(existing) => {
+                let changed = existing.protocol != mapper.protocol
+                    || existing.protocol_mapper != mapper.protocol_mapper
+                    || existing.config != mapper.config
+                    || (mapper.consent_required.is_some()
+                        && existing.consent_required != mapper.consent_required)
+                    || (mapper.consent_text.is_some()
+                        && existing.consent_text != mapper.consent_text);
+                if changed {
+                    let mut mapper = mapper.clone();
+                    mapper.id.clone_from(&existing.id);
+                    updated.push(mapper);
+                }
+            }
+        }
+    }
+    let removed = current
+        .iter()
+        .filter(|m| !desired.iter().any(|d| d.name == m.name))
+        .cloned()
+        .collect();
+    (added, updated, removed)
+}
+
+/// compare client scopes by name, returning the names to add and the scopes to remove
+fn scope_changes(
+    current: &[ClientScopeRepresentation],
+    desired: &[String],
+) -> (Vec<String>, Vec<ClientScopeRepresentation>) {
+    let added = desired
+        .iter()
+        .filter(|name| !current.iter().any(|s| s.name.as_ref() == Some(*name)))
+        .cloned()
+        .collect();
+    let removed = current
+        .iter()
+        .filter(|s| !s.name.as_ref().is_some_and(|name| desired.contains(name)))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+fn scope_id(scope: &ClientScopeRepresentation) -> Result<&str> {
+    scope
+        .id
+        .as_deref()
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))
+}
+
+fn secret_from_credential(credential: CredentialRepresentation) -> Result<ClientSecret> {
+    credential
+        .value
+        .map(ClientSecret::new)
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingField("value".into())))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rest::types::{ClientRepresentation, ProtocolMapperRepresentation};
+
+    #[test]
+    fn test_merge_client() {
+        let existing: ClientRepresentation = serde_json::from_value(serde_json::json!({
+            "id": "uuid",
+            "clientId": "client",
+            "enabled": true,
+            "redirectUris": ["https://example.com/*"],
+            "attributes": { "a": "1", "b": "2" },
+        }))
+        .unwrap();
+        let desired: ClientRepresentation = serde_json::from_value(serde_json::json!({
+            "clientId": "client",
+            "enabled": false,
+            "attributes": { "b": "2", "c": "3" },
+            "defaultClientScopes": ["profile"],
+        }))
+        .unwrap();
+
+        let (merged, fields) = super::merge_client(&existing, &desired).unwrap();
+        assert_eq!(fields, ["attributes.c", "enabled"]);
+        assert_eq!(merged.id.as_deref(), Some("uuid"));
+        assert_eq!(merged.enabled, Some(false));
+        assert_eq!(merged.redirect_uris, ["https://example.com/*"]);
+        assert_eq!(merged.attributes.len(), 3);
+        assert!(merged.default_client_scopes.is_empty());
+
+        let (_, fields) = super::merge_client(&merged, &desired).unwrap();
+        assert!(fields.is_empty());
+    }
+
+    #[test]
+    fn test_protocol_mapper_changes() {
+        let mapper = |id: Option<&str>, name: &str, claim: &str| {
+            serde_json::from_value::<ProtocolMapperRepresentation>(serde_json::json!({
+                "id": id,
+                "name": name,
+                "protocol": "openid-connect",
+                "protocolMapper": "oidc-hardcoded-claim-mapper",
+                "config": { "claim.name": claim },
+            }))
+            .unwrap()
+        };
+        let current = [
+            mapper(Some("1"), "same", "a"),
+            mapper(Some("2"), "changed", "b"),
+            mapper(Some("3"), "removed", "c"),
+        ];
+        let desired = [
+            mapper(None, "same", "a"),
+            mapper(None, "changed", "x"),
+            mapper(None, "added", "d"),
+        ];
+
+        let (added, updated, removed) = super::protocol_mapper_changes(&current, &desired);
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].name.as_deref(), Some("added"));
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].id.as_deref(), Some("2"));
+        assert_eq!(updated[0].config["claim.name"], "x");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id.as_deref(), Some("3"));
+    }
+}
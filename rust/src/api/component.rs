@@ -0,0 +1,193 @@
+use std::future::Future;
+
+use reqwest::StatusCode;
+
+use crate::{
+    error::ResourceType,
+    rest::types::{ComponentRepresentation, ComponentTypeRepresentation},
+    util::RequestBuilderExt,
+    Error, ErrorKind,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// component-related methods of the keycloak api
+///
+/// components are the configured instances of keycloak's providers, e.g. user federation
+/// providers (`org.keycloak.storage.UserStorageProvider`), their mappers and key providers
+/// (`org.keycloak.keys.KeyProvider`)
+pub trait KeycloakComponentExt {
+    /// get the components of the realm
+    ///
+    /// `parent` is the id of the parent component (or of the realm for top-level components),
+    /// `provider_type` the provider type (e.g. `org.keycloak.keys.KeyProvider`)
+    fn components(
+        &self,
+        parent: Option<&str>,
+        provider_type: Option<&str>,
+        name: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<ComponentRepresentation>>> + Send;
+
+    /// get a single component given its id
+    fn component(
+        &self,
+        component_id: &str,
+    ) -> impl Future<Output = Result<ComponentRepresentation>> + Send;
+
+    /// create a component, returning its id
+    ///
+    /// the `parent_id` of top-level components is the id of the realm
+    fn create_component(
+        &self,
+        component: &ComponentRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// update an existing component
+    fn update_component(
+        &self,
+        component_id: &str,
+        component: &ComponentRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a component including its sub-components
+    fn delete_component(&self, component_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the types of sub-components that can be created below a component
+    ///
+    /// e.g. the mapper types (`org.keycloak.storage.ldap.mappers.LDAPStorageMapper`) of an ldap
+    /// user federation provider
+    fn component_sub_component_types(
+        &self,
+        component_id: &str,
+        provider_type: &str,
+    ) -> impl Future<Output = Result<Vec<ComponentTypeRepresentation>>> + Send;
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakComponentExt for crate::Keycloak<A> {
+    #[tracing::instrument(skip(self))]
+    async fn components(
+        &self,
+        parent: Option<&str>,
+        provider_type: Option<&str>,
+        name: Option<&str>,
+    ) -> Result<Vec<ComponentRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying components");
+        let response = api_client
+            .get_realm_components()
+            .realm(&self.config.realm)
+            .set_opt(parent, |r, v| r.parent(v))
+            .set_opt(provider_type, |r, v| r.type_(v))
+            .set_opt(name, |r, v| r.name(v))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn component(&self, component_id: &str) -> Result<ComponentRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying component");
+        let response = api_client
+            .get_realm_components_id()
+            .realm(&self.config.realm)
+            .id(component_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(not_found)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, component), fields(name = component.name))]
+    async fn create_component(&self, component: &ComponentRepresentation) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating component");
+        let response = api_client
+            .post_realm_components()
+            .realm(&self.config.realm)
+            .body(component.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self, component))]
+    async fn update_component(
+        &self,
+        component_id: &str,
+        component: &ComponentRepresentation,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating component");
+        api_client
+            .put_realm_components_id()
+            .realm(&self.config.realm)
+            .id(component_id)
+            .body(component.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_component(&self, component_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting component");
+        api_client
+            .delete_realm_components_id()
+            .realm(&self.config.realm)
+            .id(component_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(not_found)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn component_sub_component_types(
+        &self,
+        component_id: &str,
+        provider_type: &str,
+    ) -> Result<Vec<ComponentTypeRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying sub-component types");
+        let response = api_client
+            .get_realm_components_id_sub_component_types()
+            .realm(&self.config.realm)
+            .id(component_id)
+            .type_(provider_type)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(not_found)?
+            .into_inner();
+        Ok(response)
+    }
+}
+
+fn not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::Component),
+    )
+}
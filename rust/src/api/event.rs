@@ -0,0 +1,691 @@
+use std::{fmt::Display, future::Future, str::FromStr};
+
+use futures_util::{stream, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    rest::types::{AdminEventRepresentation, EventRepresentation},
+    Error, ErrorKind,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// enum of the identifiers keycloak uses for a kind of event, with a fallback for values not
+/// covered by the variants
+macro_rules! event_enum {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($variant:ident => $value:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        $vis enum $name {
+            $($variant,)*
+            /// any other value, e.g. added by a newer keycloak version or an extension
+            Other(String),
+        }
+
+        impl $name {
+            fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+    };
+}
+
+/// a day used to filter events by date
+///
+/// keycloak expects dates as `yyyy-MM-dd`, this makes sure only valid dates are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl EventDate {
+    /// `None` if there is no such day
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let leap_year =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap_year => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then_some(Self { year, month, day })
+    }
+}
+
+impl Display for EventDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for EventDate {
+    type Err = Error;
+
+    /// parse a date given as `yyyy-MM-dd`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::new_kind(ErrorKind::WrongType(
+                "date (yyyy-MM-dd)".into(),
+                s.to_string(),
+            ))
+        };
+        let mut parts = s.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let digits =
+            |part: &str, len| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+        if !(digits(year, 4) && digits(month, 2) && digits(day, 2)) {
+            return Err(invalid());
+        }
+        let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse()) else {
+            return Err(invalid());
+        };
+        Self::new(year, month, day).ok_or_else(invalid)
+    }
+}
+
+/// kind of change recorded by an admin event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationType {
+    Create,
+    Update,
+    Delete,
+    /// e.g. sending a verify email or clearing a user's brute force status
+    Action,
+}
+
+impl OperationType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "CREATE",
+            Self::Update => "UPDATE",
+            Self::Delete => "DELETE",
+            Self::Action => "ACTION",
+        }
+    }
+}
+
+event_enum! {
+    /// type of the resource changed by an admin event
+    pub enum AdminResourceType {
+        Realm => "REALM",
+        RealmRole => "REALM_ROLE",
+        RealmRoleMapping => "REALM_ROLE_MAPPING",
+        RealmScopeMapping => "REALM_SCOPE_MAPPING",
+        AuthFlow => "AUTH_FLOW",
+        AuthExecutionFlow => "AUTH_EXECUTION_FLOW",
+        AuthExecution => "AUTH_EXECUTION",
+        AuthenticatorConfig => "AUTHENTICATOR_CONFIG",
+        RequiredAction => "REQUIRED_ACTION",
+        IdentityProvider => "IDENTITY_PROVIDER",
+        IdentityProviderMapper => "IDENTITY_PROVIDER_MAPPER",
+        ProtocolMapper => "PROTOCOL_MAPPER",
+        User => "USER",
+        UserLoginFailure => "USER_LOGIN_FAILURE",
+        UserSession => "USER_SESSION",
+        UserFederationProvider => "USER_FEDERATION_PROVIDER",
+        UserFederationMapper => "USER_FEDERATION_MAPPER",
+        Group => "GROUP",
+        GroupMembership => "GROUP_MEMBERSHIP",
+        Client => "CLIENT",
+        ClientInitialAccessModel => "CLIENT_INITIAL_ACCESS_MODEL",
+        ClientRole => "CLIENT_ROLE",
+        ClientRoleMapping => "CLIENT_ROLE_MAPPING",
+        ClientScope => "CLIENT_SCOPE",
+        ClientScopeMapping => "CLIENT_SCOPE_MAPPING",
+        ClientScopeClientMapping => "CLIENT_SCOPE_CLIENT_MAPPING",
+        ClusterNode => "CLUSTER_NODE",
+        Component => "COMPONENT",
+        AuthorizationResourceServer => "AUTHORIZATION_RESOURCE_SERVER",
+        AuthorizationResource => "AUTHORIZATION_RESOURCE",
+        AuthorizationScope => "AUTHORIZATION_SCOPE",
+        AuthorizationPolicy => "AUTHORIZATION_POLICY",
+        Organization => "ORGANIZATION",
+        OrganizationMembership => "ORGANIZATION_MEMBERSHIP",
+        Custom => "CUSTOM",
+    }
+}
+
+/// filters for [`admin_events`](KeycloakEventExt::admin_events)
+///
+/// all filters are combined, so only events matching every filter are returned. events are
+/// returned newest first.
+#[derive(Debug, Clone, Default)]
+pub struct AdminEventQuery {
+    operation_types: Vec<OperationType>,
+    resource_types: Vec<AdminResourceType>,
+    resource_path: Option<String>,
+    auth_realm: Option<String>,
+    auth_client: Option<String>,
+    auth_user: Option<String>,
+    auth_ip_address: Option<String>,
+    date_from: Option<EventDate>,
+    date_to: Option<EventDate>,
+    first: Option<i32>,
+    max: Option<i32>,
+}
+
+impl AdminEventQuery {
+    /// no filters, matches the first 100 admin events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// only return events of the given operation type, may be given multiple times
+    pub fn operation_type(mut self, operation_type: OperationType) -> Self {
+        self.operation_types.push(operation_type);
+        self
+    }
+
+    /// only return events of the given resource type, may be given multiple times
+    pub fn resource_type(mut self, resource_type: AdminResourceType) -> Self {
+        self.resource_types.push(resource_type);
+        self
+    }
+
+    /// only return events of resources below the given path (e.g. `users/*`)
+    pub fn resource_path(mut self, resource_path: &str) -> Self {
+        self.resource_path = Some(resource_path.into());
+        self
+    }
+
+    /// id of the realm of the admin performing the operation
+    pub fn auth_realm(mut self, auth_realm: &str) -> Self {
+        self.auth_realm = Some(auth_realm.into());
+        self
+    }
+
+    /// id of the client the admin used
+    pub fn auth_client(mut self, auth_client: &str) -> Self {
+        self.auth_client = Some(auth_client.into());
+        self
+    }
+
+    /// id of the admin user
+    pub fn auth_user(mut self, auth_user: &str) -> Self {
+        self.auth_user = Some(auth_user.into());
+        self
+    }
+
+    pub fn auth_ip_address(mut self, auth_ip_address: &str) -> Self {
+        self.auth_ip_address = Some(auth_ip_address.into());
+        self
+    }
+
+    /// only return events from this day on
+    pub fn date_from(mut self, date_from: EventDate) -> Self {
+        self.date_from = Some(date_from);
+        self
+    }
+
+    /// only return events up to this day (inclusive)
+    pub fn date_to(mut self, date_to: EventDate) -> Self {
+        self.date_to = Some(date_to);
+        self
+    }
+
+    /// skip the first `first` matching events
+    pub fn first(mut self, first: i32) -> Self {
+        self.first = Some(first);
+        self
+    }
+
+    /// return at most `max` events (keycloak defaults to 100)
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = self.filter_params();
+        let ints = [("first", self.first), ("max", self.max)];
+        for (name, value) in ints {
+            if let Some(value) = value {
+                params.push((name, value.to_string()));
+            }
+        }
+        params
+    }
+
+    /// the params without pagination
+    fn filter_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        for operation_type in &self.operation_types {
+            params.push(("operationTypes", operation_type.as_str().to_string()));
+        }
+        for resource_type in &self.resource_types {
+            params.push(("resourceTypes", resource_type.as_str().to_string()));
+        }
+        let strings = [
+            ("resourcePath", &self.resource_path),
+            ("authRealm", &self.auth_realm),
+            ("authClient", &self.auth_client),
+            ("authUser", &self.auth_user),
+            ("authIpAddress", &self.auth_ip_address),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                params.push((name, value.clone()));
+            }
+        }
+        let dates = [("dateFrom", self.date_from), ("dateTo", self.date_to)];
+        for (name, value) in dates {
+            if let Some(value) = value {
+                params.push((name, value.to_string()));
+            }
+        }
+        params
+    }
+}
+
+event_enum! {
+    /// type of a (login) event
+    ///
+    /// most types have a corresponding `*_ERROR` type for failed attempts
+    pub enum EventType {
+        Login => "LOGIN",
+        LoginError => "LOGIN_ERROR",
+        Register => "REGISTER",
+        RegisterError => "REGISTER_ERROR",
+        Logout => "LOGOUT",
+        LogoutError => "LOGOUT_ERROR",
+        CodeToToken => "CODE_TO_TOKEN",
+        CodeToTokenError => "CODE_TO_TOKEN_ERROR",
+        ClientLogin => "CLIENT_LOGIN",
+        ClientLoginError => "CLIENT_LOGIN_ERROR",
+        RefreshToken => "REFRESH_TOKEN",
+        RefreshTokenError => "REFRESH_TOKEN_ERROR",
+        IntrospectToken => "INTROSPECT_TOKEN",
+        IntrospectTokenError => "INTROSPECT_TOKEN_ERROR",
+        TokenExchange => "TOKEN_EXCHANGE",
+        TokenExchangeError => "TOKEN_EXCHANGE_ERROR",
+        UpdateProfile => "UPDATE_PROFILE",
+        UpdateProfileError => "UPDATE_PROFILE_ERROR",
+        UpdatePassword => "UPDATE_PASSWORD",
+        UpdatePasswordError => "UPDATE_PASSWORD_ERROR",
+        UpdateTotp => "UPDATE_TOTP",
+        UpdateTotpError => "UPDATE_TOTP_ERROR",
+        RemoveTotp => "REMOVE_TOTP",
+        RemoveTotpError => "REMOVE_TOTP_ERROR",
+        VerifyEmail => "VERIFY_EMAIL",
+        VerifyEmailError => "VERIFY_EMAIL_ERROR",
+        SendVerifyEmail => "SEND_VERIFY_EMAIL",
+        SendVerifyEmailError => "SEND_VERIFY_EMAIL_ERROR",
+        SendResetPassword => "SEND_RESET_PASSWORD",
+        SendResetPasswordError => "SEND_RESET_PASSWORD_ERROR",
+        ResetPassword => "RESET_PASSWORD",
+        ResetPasswordError => "RESET_PASSWORD_ERROR",
+        ExecuteActions => "EXECUTE_ACTIONS",
+        ExecuteActionsError => "EXECUTE_ACTIONS_ERROR",
+        ExecuteActionToken => "EXECUTE_ACTION_TOKEN",
+        ExecuteActionTokenError => "EXECUTE_ACTION_TOKEN_ERROR",
+        IdentityProviderLogin => "IDENTITY_PROVIDER_LOGIN",
+        IdentityProviderLoginError => "IDENTITY_PROVIDER_LOGIN_ERROR",
+        IdentityProviderFirstLogin => "IDENTITY_PROVIDER_FIRST_LOGIN",
+        IdentityProviderFirstLoginError => "IDENTITY_PROVIDER_FIRST_LOGIN_ERROR",
+        IdentityProviderLinkAccount => "IDENTITY_PROVIDER_LINK_ACCOUNT",
+        IdentityProviderLinkAccountError => "IDENTITY_PROVIDER_LINK_ACCOUNT_ERROR",
+        Impersonate => "IMPERSONATE",
+        ImpersonateError => "IMPERSONATE_ERROR",
+        DeleteAccount => "DELETE_ACCOUNT",
+        DeleteAccountError => "DELETE_ACCOUNT_ERROR",
+    }
+}
+
+/// filters for [`events`](KeycloakEventExt::events)
+///
+/// all filters are combined, so only events matching every filter are returned. events are
+/// returned newest first.
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    types: Vec<EventType>,
+    user: Option<String>,
+    client: Option<String>,
+    ip_address: Option<String>,
+    date_from: Option<EventDate>,
+    date_to: Option<EventDate>,
+    first: Option<i32>,
+    max: Option<i32>,
+}
+
+impl EventQuery {
+    /// no filters, matches the first 100 events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// only return events of the given type, may be given multiple times
+    pub fn event_type(mut self, event_type: EventType) -> Self {
+        self.types.push(event_type);
+        self
+    }
+
+    /// id of the user
+    pub fn user(mut self, user: &str) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// client id (not the id) of the client
+    pub fn client(mut self, client: &str) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn ip_address(mut self, ip_address: &str) -> Self {
+        self.ip_address = Some(ip_address.into());
+        self
+    }
+
+    /// only return events from this day on
+    pub fn date_from(mut self, date_from: EventDate) -> Self {
+        self.date_from = Some(date_from);
+        self
+    }
+
+    /// only return events up to this day (inclusive)
+    pub fn date_to(mut self, date_to: EventDate) -> Self {
+        self.date_to = Some(date_to);
+        self
+    }
+
+    /// skip the first `first` matching events
+    pub fn first(mut self, first: i32) -> Self {
+        self.first = Some(first);
+        self
+    }
+
+    /// return at most `max` events (keycloak defaults to 100)
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = self.filter_params();
+        let ints = [("first", self.first), ("max", self.max)];
+        for (name, value) in ints {
+            if let Some(value) = value {
+                params.push((name, value.to_string()));
+            }
+        }
+        params
+    }
+
+    /// the params without pagination
+    fn filter_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        for event_type in &self.types {
+            params.push(("type", event_type.as_str().to_string()));
+        }
+        let strings = [
+            ("user", &self.user),
+            ("client", &self.client),
+            ("ipAddress", &self.ip_address),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                params.push((name, value.clone()));
+            }
+        }
+        let dates = [("dateFrom", self.date_from), ("dateTo", self.date_to)];
+        for (name, value) in dates {
+            if let Some(value) = value {
+                params.push((name, value.to_string()));
+            }
+        }
+        params
+    }
+}
+
+/// event-related methods of the keycloak api
+///
+/// events are only recorded if enabled in the realm's event settings
+pub trait KeycloakEventExt {
+    /// get a page of the admin events matching the query
+    fn admin_events(
+        &self,
+        query: &AdminEventQuery,
+    ) -> impl Future<Output = Result<Vec<AdminEventRepresentation>>> + Send;
+
+    /// stream all admin events matching the query
+    ///
+    /// like [`users_stream`](super::KeycloakUserExt::users_stream), this only fetches the next page
+    /// of events when the previous one has been consumed. the stream starts at the query's
+    /// `first` event, its `max` is ignored.
+    fn admin_events_stream<'a>(
+        &'a self,
+        query: &'a AdminEventQuery,
+    ) -> impl Stream<Item = Result<AdminEventRepresentation>> + Send + 'a;
+
+    /// get a page of the (login) events matching the query
+    fn events(
+        &self,
+        query: &EventQuery,
+    ) -> impl Future<Output = Result<Vec<EventRepresentation>>> + Send;
+
+    /// stream all (login) events matching the query
+    ///
+    /// see [`admin_events_stream`](Self::admin_events_stream)
+    fn events_stream<'a>(
+        &'a self,
+        query: &'a EventQuery,
+    ) -> impl Stream<Item = Result<EventRepresentation>> + Send + 'a;
+
+    /// delete all admin events of the realm
+    fn clear_admin_events(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete all (login) events of the realm
+    fn clear_events(&self) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakEventExt for crate::Keycloak<A> {
+    #[tracing::instrument(skip(self))]
+    async fn admin_events(&self, query: &AdminEventQuery) -> Result<Vec<AdminEventRepresentation>> {
+        tracing::debug!("querying admin events");
+        query_events(self, "admin-events", &query.params()).await
+    }
+
+    fn admin_events_stream<'a>(
+        &'a self,
+        query: &'a AdminEventQuery,
+    ) -> impl Stream<Item = Result<AdminEventRepresentation>> + Send + 'a {
+        let first = query.first.unwrap_or(0);
+        events_stream(first, move |first, max| async move {
+            tracing::debug!(first, "querying page of admin events");
+            let mut params = query.filter_params();
+            params.extend([("first", first.to_string()), ("max", max.to_string())]);
+            query_events(self, "admin-events", &params).await
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn events(&self, query: &EventQuery) -> Result<Vec<EventRepresentation>> {
+        tracing::debug!("querying events");
+        query_events(self, "events", &query.params()).await
+    }
+
+    fn events_stream<'a>(
+        &'a self,
+        query: &'a EventQuery,
+    ) -> impl Stream<Item = Result<EventRepresentation>> + Send + 'a {
+        let first = query.first.unwrap_or(0);
+        events_stream(first, move |first, max| async move {
+            tracing::debug!(first, "querying page of events");
+            let mut params = query.filter_params();
+            params.extend([("first", first.to_string()), ("max", max.to_string())]);
+            query_events(self, "events", &params).await
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn clear_admin_events(&self) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting admin events");
+        api_client
+            .delete_realm_admin_events()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn clear_events(&self) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting events");
+        api_client
+            .delete_realm_events()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+}
+
+/// query one of the event endpoints (`admin-events` or `events`) of the realm
+async fn query_events<A: crate::AuthenticationProvider + Send + Sync, T: DeserializeOwned>(
+    keycloak: &crate::Keycloak<A>,
+    endpoint: &str,
+    params: &[(&'static str, String)],
+) -> Result<Vec<T>> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    // the generated client doesn't support the array parameters of these endpoints
+    let response = api_client
+        .client
+        .get(keycloak.config.admin_url(&[endpoint])?)
+        .query(params)
+        .send()
+        .await
+        .map_err(crate::error::reqwest)?;
+    if !response.status().is_success() {
+        return Err(crate::error::error_response(response).await);
+    }
+    let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+    serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+}
+
+/// stream events page by page, starting at `first`
+///
+/// `query_page` is called with the offset and size of each page
+fn events_stream<T, F, Fut>(first: i32, query_page: F) -> impl Stream<Item = Result<T>> + Send
+where
+    T: Send,
+    F: Fn(i32, i32) -> Fut + Send,
+    Fut: Future<Output = Result<Vec<T>>> + Send,
+{
+    const PAGE_MAX: i32 = 100;
+
+    let pages = stream::try_unfold(Some(first), move |page_offset| {
+        let page = page_offset.map(|first| (first, query_page(first, PAGE_MAX)));
+        async move {
+            let Some((first, page)) = page else {
+                return Ok(None);
+            };
+            let page = page.await?;
+            // next page
+            let page_offset = (page.len() == PAGE_MAX as usize).then_some(first + PAGE_MAX);
+            Ok(Some((page, page_offset)))
+        }
+    });
+    pages
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        AdminEventQuery, AdminResourceType, EventDate, EventQuery, EventType, OperationType,
+    };
+
+    #[test]
+    fn test_admin_event_query_params() {
+        assert!(AdminEventQuery::new().params().is_empty());
+
+        let query = AdminEventQuery::new()
+            .operation_type(OperationType::Create)
+            .operation_type(OperationType::Delete)
+            .resource_type(AdminResourceType::User)
+            .resource_type(AdminResourceType::Other("CUSTOM_THING".into()))
+            .auth_user("admin")
+            .date_from(EventDate::new(2024, 1, 1).unwrap())
+            .max(10);
+        assert_eq!(
+            query.params(),
+            [
+                ("operationTypes", "CREATE".to_string()),
+                ("operationTypes", "DELETE".to_string()),
+                ("resourceTypes", "USER".to_string()),
+                ("resourceTypes", "CUSTOM_THING".to_string()),
+                ("authUser", "admin".to_string()),
+                ("dateFrom", "2024-01-01".to_string()),
+                ("max", "10".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_date() {
+        assert_eq!(
+            "2024-02-29".parse::<EventDate>().unwrap(),
+            EventDate::new(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            EventDate::new(2024, 3, 5).unwrap().to_string(),
+            "2024-03-05"
+        );
+        assert_eq!(EventDate::new(2023, 2, 29), None);
+        assert_eq!(EventDate::new(2024, 13, 1), None);
+        assert_eq!(EventDate::new(2024, 4, 0), None);
+        for invalid in [
+            "2024-1-01",
+            "2024-01-01-01",
+            "01.01.2024",
+            "2024-04-31",
+            "+024-01-01",
+        ] {
+            assert!(invalid.parse::<EventDate>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_event_query_params() {
+        let query = EventQuery::new()
+            .event_type(EventType::Login)
+            .event_type(EventType::Other("CUSTOM_REQUIRED_ACTION".into()))
+            .client("app")
+            .date_to(EventDate::new(2024, 12, 31).unwrap())
+            .first(20);
+        assert_eq!(
+            query.params(),
+            [
+                ("type", "LOGIN".to_string()),
+                ("type", "CUSTOM_REQUIRED_ACTION".to_string()),
+                ("client", "app".to_string()),
+                ("dateTo", "2024-12-31".to_string()),
+                ("first", "20".to_string()),
+            ]
+        );
+    }
+}
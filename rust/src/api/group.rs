@@ -1,33 +1,358 @@
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+
+use super::Ensured;
 use crate::{
-    rest::types::{GroupRepresentation, RoleRepresentation, UserRepresentation},
-    Error,
+    error::ResourceType,
+    rest::{
+        builder::GetRealmGroups,
+        types::{
+            Attributes, GroupRepresentation, HasAttributes, ManagementPermissionReference,
+            RoleRepresentation, UserRepresentation,
+        },
+    },
+    util::RequestBuilderExt,
+    Error, ErrorKind,
 };
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// filters for [`search_groups`](KeycloakGroupExt::search_groups)
+///
+/// all filters are combined, so only groups matching every filter are returned
+#[derive(Debug, Clone, Default)]
+pub struct GroupQuery {
+    search: Option<String>,
+    exact: Option<bool>,
+    populate_hierarchy: Option<bool>,
+    attributes: Vec<(String, String)>,
+    brief: Option<bool>,
+}
+
+impl GroupQuery {
+    /// no filters, matches all groups
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// search string matched against the group names (including subgroups)
+    pub fn search(mut self, search: &str) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    /// match the group name exactly instead of as substring
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = Some(exact);
+        self
+    }
+
+    /// return all subgroups of the matching groups' ancestors instead of only the path to the
+    /// matching groups (keycloak's default)
+    pub fn populate_hierarchy(mut self, populate_hierarchy: bool) -> Self {
+        self.populate_hierarchy = Some(populate_hierarchy);
+        self
+    }
+
+    /// only return groups with the given attribute value
+    ///
+    /// keycloak separates multiple attribute filters using spaces, so neither key nor value may
+    /// contain spaces or colons
+    pub fn attribute(mut self, key: &str, value: &str) -> Self {
+        self.attributes.push((key.into(), value.into()));
+        self
+    }
+
+    /// only return the basic group fields (no attributes etc.)
+    pub fn brief(mut self, brief: bool) -> Self {
+        self.brief = Some(brief);
+        self
+    }
+
+    /// the `q` parameter for the attribute filters
+    fn attribute_query(&self) -> Option<String> {
+        if self.attributes.is_empty() {
+            return None;
+        }
+        let query = self
+            .attributes
+            .iter()
+            .map(|(key, value)| format!("{key}:{value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(query)
+    }
+
+    fn apply<'a>(&self, request: GetRealmGroups<'a>) -> GetRealmGroups<'a> {
+        request
+            .set_opt(self.search.clone(), |r, v| r.search(v))
+            .set_opt(self.exact, |r, v| r.exact(v))
+            .set_opt(self.populate_hierarchy, |r, v| r.populate_hierarchy(v))
+            .set_opt(self.attribute_query(), |r, v| r.q(v))
+            .set_opt(self.brief, |r, v| r.brief_representation(v))
+    }
+}
+
+/// how [`delete_group_recursive`](KeycloakGroupExt::delete_group_recursive) handles members
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupMembers {
+    /// delete the groups anyway, keycloak removes the memberships implicitly
+    #[default]
+    Ignore,
+    /// fail with [`ErrorKind::NotEmpty`] before deleting anything if any of the groups has members
+    Refuse,
+    /// remove the members from every group before deleting it
+    Detach,
+}
+
+/// options for [`delete_group_recursive`](KeycloakGroupExt::delete_group_recursive)
+#[derive(Debug, Clone, Default)]
+pub struct DeleteGroupOptions {
+    pub members: GroupMembers,
+}
+
+/// changes made by [`sync_group_members`](KeycloakGroupExt::sync_group_members)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MembershipChanges {
+    /// ids of the users added to the group
+    pub added: Vec<String>,
+    /// ids of the users removed from the group
+    pub removed: Vec<String>,
+}
+
+impl MembershipChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// group-related methods of the keycloak api
 pub trait KeycloakGroupExt {
-    /// get a single group matching the given name
+    /// get a single group matching the given name exactly
+    ///
+    /// subgroups are searched as well. group names are only unique among siblings, so this fails
+    /// with [`ErrorKind::NotUnique`] if multiple groups (at any depth) have the name instead of
+    /// picking one of them.
     fn group_by_name(
         &self,
         group_name: &str,
     ) -> impl Future<Output = Result<GroupRepresentation>> + Send;
 
+    /// get all groups matching the given filters
+    ///
+    /// keycloak returns the top-level groups containing matching groups, with the path to the
+    /// matching subgroups in `sub_groups`
+    fn search_groups(
+        &self,
+        query: &GroupQuery,
+    ) -> impl Future<Output = Result<Vec<GroupRepresentation>>> + Send;
+
+    /// get a single group using its path (e.g. `/parent/child`)
+    fn group_by_path(&self, path: &str)
+        -> impl Future<Output = Result<GroupRepresentation>> + Send;
+
     /// get a single group using its uuid
     fn group_by_id(
         &self,
         group_id: &str,
     ) -> impl Future<Output = Result<GroupRepresentation>> + Send;
 
-    /// get all users in a group
-    fn group_users(
+    /// get a page of the direct subgroups of a group
+    ///
+    /// newer keycloak versions don't populate `sub_groups` when listing groups anymore, use this
+    /// (or [`all_group_children`](KeycloakGroupExt::all_group_children)) instead
+    fn group_children(
+        &self,
+        group_id: &str,
+        first: Option<i32>,
+        max: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<GroupRepresentation>>> + Send;
+
+    /// get the direct subgroup of a group with the given name
+    ///
+    /// this only queries the matching subgroup instead of all of them
+    fn group_find_child(
+        &self,
+        parent_id: &str,
+        name: &str,
+    ) -> impl Future<Output = Result<GroupRepresentation>> + Send;
+
+    /// get all direct subgroups of a group
+    fn all_group_children(
+        &self,
+        group_id: &str,
+    ) -> impl Future<Output = Result<Vec<GroupRepresentation>>> + Send;
+
+    /// get all groups of the realm with their subgroups
+    ///
+    /// unlike the group listing of keycloak, `sub_groups` is populated recursively for every
+    /// group. subgroups are fetched level by level, with several requests running concurrently.
+    fn group_tree(&self) -> impl Future<Output = Result<Vec<GroupRepresentation>>> + Send;
+
+    /// count the groups of the realm
+    ///
+    /// if `top` is `true`, only top-level groups are counted. if a `search` string is given, only
+    /// groups whose name contains it are counted
+    fn groups_count(
+        &self,
+        search: Option<&str>,
+        top: bool,
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// create a new top-level group, returning its id
+    fn create_group(
+        &self,
+        group: &GroupRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// create a new subgroup of the given parent group, returning its id
+    fn group_create_child(
+        &self,
+        parent_id: &str,
+        group: &GroupRepresentation,
+    ) -> impl Future<Output = Result<String>> + Send;
+
+    /// move a group (including its subgroups) below a new parent group
+    ///
+    /// if `new_parent` is `None`, the group is moved to the top level
+    fn move_group(
+        &self,
+        group_id: &str,
+        new_parent: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get a group or create it if it doesn't exist yet
+    ///
+    /// `path_or_name` is either a group path (starting with `/`), in which case missing parent
+    /// groups are created as well, or the name of a group (searched in the whole hierarchy and
+    /// created as top-level group if missing, even if the name contains `/`). the given `attributes` are set on the group,
+    /// other attributes of existing groups are kept.
+    fn ensure_group(
+        &self,
+        path_or_name: &str,
+        attributes: &HashMap<String, Vec<String>>,
+    ) -> impl Future<Output = Result<Ensured>> + Send;
+
+    /// update an existing group
+    ///
+    /// this doesn't change the subgroups of the group. the attributes are replaced by the ones of
+    /// `group`, so empty attributes remove all existing ones. fails with [`ErrorKind::Conflict`] if
+    /// a sibling already has the group's name.
+    fn update_group(
+        &self,
+        group_id: &str,
+        group: &GroupRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// modify an existing group
+    ///
+    /// this fetches the current group, applies `modify` and writes the result back, so fields not
+    /// touched by `modify` keep their current values. returns the updated group.
+    fn modify_group<F>(
+        &self,
+        group_id: &str,
+        modify: F,
+    ) -> impl Future<Output = Result<GroupRepresentation>> + Send
+    where
+        F: FnOnce(&mut GroupRepresentation) + Send;
+
+    /// get the attributes of a group
+    fn group_attributes(
+        &self,
+        group_id: &str,
+    ) -> impl Future<Output = Result<Attributes<HashMap<String, Vec<String>>>>> + Send;
+
+    /// modify the attributes of a group, see [`modify_group`](KeycloakGroupExt::modify_group)
+    ///
+    /// returns the updated attributes
+    fn modify_group_attributes<F>(
+        &self,
+        group_id: &str,
+        modify: F,
+    ) -> impl Future<Output = Result<Attributes<HashMap<String, Vec<String>>>>> + Send
+    where
+        F: FnOnce(&mut Attributes<&mut HashMap<String, Vec<String>>>) + Send;
+
+    /// set the given attributes of a group, replacing their values
+    ///
+    /// other attributes of the group are kept
+    fn merge_group_attributes(
+        &self,
+        group_id: &str,
+        attributes: &HashMap<String, Vec<String>>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a group (including its subgroups)
+    fn delete_group(&self, group_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a group and its subgroups, deleting the subgroups first
+    ///
+    /// members of the groups are handled according to `options`
+    fn delete_group_recursive(
+        &self,
+        group_id: &str,
+        options: &DeleteGroupOptions,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// make the given users the only direct members of a group
+    ///
+    /// only the users missing from the group are added and only the members not in
+    /// `desired_user_ids` are removed. if `dry_run` is `true`, the changes are only computed, not
+    /// applied.
+    fn sync_group_members(
+        &self,
+        group_id: &str,
+        desired_user_ids: &[&str],
+        dry_run: bool,
+    ) -> impl Future<Output = Result<MembershipChanges>> + Send;
+
+    /// get all direct members of a group
+    ///
+    /// members of subgroups are not included
+    fn group_members(
         &self,
         group_id: &str,
         brief_representation: Option<bool>,
     ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
 
+    /// get all direct members of a group
+    #[deprecated(note = "use `group_members` instead")]
+    fn group_users(
+        &self,
+        group_id: &str,
+        brief_representation: Option<bool>,
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send {
+        self.group_members(group_id, brief_representation)
+    }
+
+    /// stream all direct members of a group
+    ///
+    /// like [`users_stream`](super::KeycloakUserExt::users_stream), this only fetches the next page
+    /// of members when the previous one has been consumed
+    fn group_members_stream<'a>(
+        &'a self,
+        group_id: &'a str,
+        brief_representation: Option<bool>,
+    ) -> impl Stream<Item = Result<UserRepresentation>> + Send + 'a;
+
+    /// get the fine-grained admin permissions of a group
+    fn group_management_permissions(
+        &self,
+        group_id: &str,
+    ) -> impl Future<Output = Result<ManagementPermissionReference>> + Send;
+
+    /// enable or disable the fine-grained admin permissions of a group
+    ///
+    /// returns the permissions including the ids of the created authorization scope permissions
+    fn update_group_management_permissions(
+        &self,
+        group_id: &str,
+        permissions: &ManagementPermissionReference,
+    ) -> impl Future<Output = Result<ManagementPermissionReference>> + Send;
+
     /// get the realm roles associated with a group
     fn group_realm_roles(
         &self,
@@ -35,57 +360,116 @@ pub trait KeycloakGroupExt {
     ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
 
     /// add new realm roles to a group
-    #[allow(clippy::ptr_arg)] // generated api client requires &Vec<T>
     fn group_add_realm_roles(
         &self,
         group_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
     ) -> impl Future<Output = Result<()>> + Send;
 
     /// remove existing realm roles from a group
-    #[allow(clippy::ptr_arg)] // generated api client requires &Vec<T>
     fn group_remove_realm_roles(
         &self,
         group_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the roles of the client with the given uuid associated with a group
+    fn group_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// add roles of the client with the given uuid to a group
+    fn group_add_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove roles of the client with the given uuid from a group
+    fn group_remove_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
     ) -> impl Future<Output = Result<()>> + Send;
 }
 
 impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate::Keycloak<A> {
     #[tracing::instrument(skip(self))]
     async fn group_by_name(&self, group_name: &str) -> Result<GroupRepresentation> {
+        let query = GroupQuery::new()
+            .search(group_name)
+            .exact(true)
+            .populate_hierarchy(false)
+            .brief(false);
+        // the search returns the matching subgroups nested in their ancestors, the ancestors' names
+        // don't necessarily match
+        let mut groups = self.search_groups(&query).await?;
+        let mut found = None;
+        while let Some(group) = groups.pop() {
+            groups.extend(group.sub_groups.iter().cloned());
+            if group.name.as_deref() == Some(group_name) {
+                if found.is_some() {
+                    return Err(Error::new_kind(ErrorKind::NotUnique(ResourceType::Group)));
+                }
+                found = Some(group);
+            }
+        }
+        found.ok_or_else(|| Error::new_kind(ErrorKind::NotFound(ResourceType::Group)))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search_groups(&self, query: &GroupQuery) -> Result<Vec<GroupRepresentation>> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying group by name");
-        // TODO: paginate this api?
-        let mut groups = api_client
-            .get_realm_groups(
-                &self.config.realm,
-                Some(false),
-                Some(true),
-                None,
-                None,
-                Some(false),
-                None,
-                Some(group_name),
-            )
+        tracing::debug!("searching groups");
+        let groups = paginate_api!(|first, max| {
+            query
+                .apply(api_client.get_realm_groups().realm(&self.config.realm))
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(groups)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_by_path(&self, path: &str) -> Result<GroupRepresentation> {
+        self.refresh_if_necessary().await?;
+        let client = self.api_client.read().await;
+        let client = &client.client;
+
+        // the generated client percent-encodes the slashes separating the path segments, so
+        // encode every segment on its own instead
+        let url = self.config.url(
+            ["admin", "realms", &self.config.realm, "group-by-path"]
+                .into_iter()
+                .chain(path.split('/').filter(|segment| !segment.is_empty())),
+        )?;
+
+        tracing::debug!("querying group by path");
+        let response = client
+            .get(url)
+            .send()
             .await
-            .map_err(crate::error::progenitor)?
-            .into_inner();
-        while !groups.is_empty() {
-            let mut sub_groups = Vec::new();
-            for group in groups {
-                if group.name.as_ref().map_or("", String::as_ref) == group_name {
-                    return Ok(group);
-                };
-                sub_groups.extend(group.sub_groups);
-            }
-            groups = sub_groups;
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await.map_status(
+                StatusCode::NOT_FOUND,
+                ErrorKind::NotFound(ResourceType::Group),
+            ));
         }
-        Err(Error::new_kind(crate::ErrorKind::NotFound(
-            crate::error::ResourceType::Group,
-        )))
+
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        let data = serde_json::from_slice(&bytes).map_err(crate::error::deserialize)?;
+        Ok(data)
     }
 
     #[tracing::instrument(skip(self))]
@@ -95,15 +479,513 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
 
         tracing::debug!("querying group by id");
         let response = api_client
-            .get_realm_group(&self.config.realm, group_id)
+            .get_realm_group()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_children(
+        &self,
+        group_id: &str,
+        first: Option<i32>,
+        max: Option<i32>,
+    ) -> Result<Vec<GroupRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying subgroups");
+        let response = api_client
+            .get_realm_group_children()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .brief_representation(false)
+            .set_opt(first, |r, v| r.first(v))
+            .set_opt(max, |r, v| r.max(v))
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn group_find_child(&self, parent_id: &str, name: &str) -> Result<GroupRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("searching subgroup by name");
+        let groups = paginate_api!(|first, max| {
+            api_client
+                .get_realm_group_children()
+                .realm(&self.config.realm)
+                .group_id(parent_id)
+                .brief_representation(false)
+                .search(name)
+                .exact(true)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        // names are unique among siblings
+        groups
+            .into_iter()
+            .find(|group| group.name.as_deref() == Some(name))
+            .ok_or_else(|| Error::new_kind(ErrorKind::NotFound(ResourceType::Group)))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn all_group_children(&self, group_id: &str) -> Result<Vec<GroupRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying all subgroups");
+        let groups = paginate_api!(|first, max| {
+            api_client
+                .get_realm_group_children()
+                .realm(&self.config.realm)
+                .group_id(group_id)
+                .brief_representation(false)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(groups)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_tree(&self) -> Result<Vec<GroupRepresentation>> {
+        const CONCURRENCY: usize = 8;
+
+        let mut groups = super::top_level_groups(self).await?;
+        // groups are addressed by the indices of their ancestors in `sub_groups`
+        let mut level: Vec<Vec<usize>> = (0..groups.len()).map(|i| vec![i]).collect();
+        while !level.is_empty() {
+            let missing: Vec<_> = level
+                .iter()
+                .filter_map(|path| {
+                    let group = group_at(&mut groups, path);
+                    // older keycloak versions already include the subgroups
+                    if !group.sub_groups.is_empty()
+                        || group.sub_group_count.unwrap_or_default() == 0
+                    {
+                        return None;
+                    }
+                    Some((path.clone(), group.id.clone()?))
+                })
+                .collect();
+            let children: Vec<_> = stream::iter(missing)
+                .map(|(path, group_id)| async move {
+                    let children = self.all_group_children(&group_id).await?;
+                    Ok::<_, Error>((path, children))
+                })
+                .buffer_unordered(CONCURRENCY)
+                .try_collect()
+                .await?;
+            for (path, children) in children {
+                group_at(&mut groups, &path).sub_groups = children;
+            }
+
+            level = level
+                .into_iter()
+                .flat_map(|path| {
+                    let count = group_at(&mut groups, &path).sub_groups.len();
+                    (0..count).map(move |i| [path.as_slice(), &[i]].concat())
+                })
+                .collect();
+        }
+        Ok(groups)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn groups_count(&self, search: Option<&str>, top: bool) -> Result<u64> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("counting groups");
+        let response = api_client
+            .get_realm_groups_count()
+            .realm(&self.config.realm)
+            .set_opt(search, |r, v| r.search(v))
+            .top(top)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        let Some(&count) = response.get("count") else {
+            return Err(Error::new_kind(ErrorKind::MissingField("count".into())));
+        };
+        super::count_from(count)
+    }
+
+    #[tracing::instrument(skip(self, group), fields(group_name = group.name))]
+    async fn create_group(&self, group: &GroupRepresentation) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating group");
+        let response = api_client
+            .post_realm_groups()
+            .realm(&self.config.realm)
+            .body(group.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Group),
+                )
+            })?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self, group), fields(group_name = group.name))]
+    async fn group_create_child(
+        &self,
+        parent_id: &str,
+        group: &GroupRepresentation,
+    ) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating subgroup");
+        let response = api_client
+            .post_realm_group_children()
+            .realm(&self.config.realm)
+            .group_id(parent_id)
+            .body(group.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Group),
+                )
+            })?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn move_group(&self, group_id: &str, new_parent: Option<&str>) -> Result<()> {
+        // keycloak moves groups when posting an existing group (identified by its id) as child
+        let group = self.group_by_id(group_id).await?;
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("moving group");
+        let result = if let Some(parent_id) = new_parent {
+            api_client
+                .post_realm_group_children()
+                .realm(&self.config.realm)
+                .group_id(parent_id)
+                .body(group)
+                .send()
+                .await
+        } else {
+            api_client
+                .post_realm_groups()
+                .realm(&self.config.realm)
+                .body(group)
+                .send()
+                .await
+        };
+        result.map_err(crate::error::progenitor).map_err(|e| {
+            e.map_status(
+                StatusCode::CONFLICT,
+                ErrorKind::Conflict(ResourceType::Group),
+            )
+        })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, attributes))]
+    async fn ensure_group(
+        &self,
+        path_or_name: &str,
+        attributes: &HashMap<String, Vec<String>>,
+    ) -> Result<Ensured> {
+        let existing = if path_or_name.starts_with('/') {
+            self.group_by_path(path_or_name).await
+        } else {
+            self.group_by_name(path_or_name).await
+        };
+        match existing {
+            Ok(group) => {
+                let group_id = group
+                    .id
+                    .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+                if attributes
+                    .iter()
+                    .any(|(key, values)| group.attributes.get(key) != Some(values))
+                {
+                    self.merge_group_attributes(&group_id, attributes).await?;
+                }
+                return Ok(Ensured::Updated(group_id));
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        if !path_or_name.starts_with('/') {
+            // names may contain `/` as well, they never denote a path
+            let group = new_group(path_or_name, attributes);
+            return match self.create_group(&group).await {
+                Ok(id) => Ok(Ensured::Created(id)),
+                Err(e) if matches!(e.kind(), ErrorKind::Conflict(_)) => {
+                    tracing::debug!("group was created concurrently");
+                    let group = self.group_by_name(path_or_name).await?;
+                    let group_id = group
+                        .id
+                        .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+                    if !attributes.is_empty() {
+                        self.merge_group_attributes(&group_id, attributes).await?;
+                    }
+                    Ok(Ensured::Updated(group_id))
+                }
+                Err(e) => Err(e),
+            };
+        }
+
+        let segments: Vec<_> = path_or_name
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let Some((name, parents)) = segments.split_last() else {
+            return Err(Error::new_kind(ErrorKind::MissingField("name".into())));
+        };
+        let mut parent_id = None;
+        for (i, parent) in parents.iter().enumerate() {
+            let path = format!("/{}", segments[..=i].join("/"));
+            let id = match self.group_by_path(&path).await {
+                Ok(group) => group
+                    .id
+                    .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?,
+                Err(e) if matches!(e.kind(), ErrorKind::NotFound(_)) => {
+                    tracing::debug!(path, "creating missing parent group");
+                    let group = new_group(parent, &HashMap::new());
+                    create_group_at(self, parent_id.as_deref(), &group, &path).await?
+                }
+                Err(e) => return Err(e),
+            };
+            parent_id = Some(id);
+        }
+        let path = format!("/{}", segments.join("/"));
+        let group = new_group(name, attributes);
+        let id = create_group_at(self, parent_id.as_deref(), &group, &path).await?;
+        Ok(Ensured::Created(id))
+    }
+
+    #[tracing::instrument(skip(self, group))]
+    async fn update_group(&self, group_id: &str, group: &GroupRepresentation) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let url = self.config.admin_url(&["groups", group_id])?;
+
+        tracing::debug!("updating group");
+        // the generated client would omit empty attributes, which keycloak ignores
+        let response = api_client
+            .client
+            .put(url)
+            .json(&group_update_body(group)?)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await.map_status(
+                StatusCode::CONFLICT,
+                ErrorKind::Conflict(ResourceType::Group),
+            ));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, modify))]
+    async fn modify_group<F>(&self, group_id: &str, modify: F) -> Result<GroupRepresentation>
+    where
+        F: FnOnce(&mut GroupRepresentation) + Send,
+    {
+        let mut group = self.group_by_id(group_id).await?;
+        modify(&mut group);
+        self.update_group(group_id, &group).await?;
+        Ok(group)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_attributes(
+        &self,
+        group_id: &str,
+    ) -> Result<Attributes<HashMap<String, Vec<String>>>> {
+        let group = self.group_by_id(group_id).await?;
+        Ok(Attributes::new(group.attributes))
+    }
+
+    #[tracing::instrument(skip(self, modify))]
+    async fn modify_group_attributes<F>(
+        &self,
+        group_id: &str,
+        modify: F,
+    ) -> Result<Attributes<HashMap<String, Vec<String>>>>
+    where
+        F: FnOnce(&mut Attributes<&mut HashMap<String, Vec<String>>>) + Send,
+    {
+        let group = self
+            .modify_group(group_id, |group| modify(&mut group.attrs_mut()))
+            .await?;
+        Ok(Attributes::new(group.attributes))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn merge_group_attributes(
+        &self,
+        group_id: &str,
+        attributes: &HashMap<String, Vec<String>>,
+    ) -> Result<()> {
+        self.modify_group_attributes(group_id, |attrs| {
+            for (key, values) in attributes {
+                attrs.set_all(key, values);
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_group(&self, group_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting group");
+        api_client
+            .delete_realm_group()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::Group),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_group_recursive(
+        &self,
+        group_id: &str,
+        options: &DeleteGroupOptions,
+    ) -> Result<()> {
+        // parents always come before their subgroups
+        let mut groups = vec![group_id.to_string()];
+        let mut index = 0;
+        while let Some(id) = groups.get(index) {
+            let children = self.all_group_children(id).await?;
+            groups.extend(children.into_iter().filter_map(|group| group.id));
+            index += 1;
+        }
+
+        match options.members {
+            GroupMembers::Ignore => {}
+            GroupMembers::Refuse => {
+                for id in &groups {
+                    let mut members = std::pin::pin!(self.group_members_stream(id, Some(true)));
+                    if members.try_next().await?.is_some() {
+                        return Err(Error::new_kind(ErrorKind::NotEmpty(ResourceType::Group)));
+                    }
+                }
+            }
+            GroupMembers::Detach => {
+                for id in &groups {
+                    for user in self.group_members(id, Some(true)).await? {
+                        let Some(user_id) = user.id else {
+                            continue;
+                        };
+                        self.refresh_if_necessary().await?;
+                        let api_client = self.api_client.read().await;
+
+                        tracing::debug!(group_id = id, user_id, "removing group member");
+                        api_client
+                            .delete_realm_user_groups_group_id()
+                            .realm(&self.config.realm)
+                            .user_id(&user_id)
+                            .group_id(id)
+                            .send()
+                            .await
+                            .map_err(crate::error::progenitor)?;
+                    }
+                }
+            }
+        }
+
+        for id in groups.iter().rev() {
+            self.delete_group(id).await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, desired_user_ids))]
+    async fn sync_group_members(
+        &self,
+        group_id: &str,
+        desired_user_ids: &[&str],
+        dry_run: bool,
+    ) -> Result<MembershipChanges> {
+        let current: Vec<_> = self
+            .group_members(group_id, Some(true))
+            .await?
+            .into_iter()
+            .filter_map(|user| user.id)
+            .collect();
+        let changes = membership_changes(&current, desired_user_ids);
+        if dry_run {
+            return Ok(changes);
+        }
+
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+        for user_id in &changes.added {
+            tracing::debug!(user_id, "adding group member");
+            api_client
+                .put_realm_user_groups_group_id()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .group_id(group_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?;
+        }
+        for user_id in &changes.removed {
+            tracing::debug!(user_id, "removing group member");
+            api_client
+                .delete_realm_user_groups_group_id()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .group_id(group_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?;
+        }
+        Ok(changes)
+    }
+
     #[tracing::instrument(skip(self, brief_representation))]
-    async fn group_users(
+    async fn group_members(
         &self,
         group_id: &str,
         brief_representation: Option<bool>,
@@ -111,16 +993,16 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying users in group");
+        tracing::debug!("querying group members");
         let users = paginate_api!(|first, max| {
             api_client
-                .get_realm_group_members(
-                    &self.config.realm,
-                    group_id,
-                    brief_representation,
-                    Some(first),
-                    Some(max),
-                )
+                .get_realm_group_members()
+                .realm(&self.config.realm)
+                .group_id(group_id)
+                .set_opt(brief_representation, |r, v| r.brief_representation(v))
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -129,6 +1011,83 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
         Ok(users)
     }
 
+    fn group_members_stream<'a>(
+        &'a self,
+        group_id: &'a str,
+        brief_representation: Option<bool>,
+    ) -> impl Stream<Item = Result<UserRepresentation>> + Send + 'a {
+        const PAGE_MAX: i32 = 100;
+
+        let pages = stream::try_unfold(Some(0), move |page_offset| async move {
+            let Some(first) = page_offset else {
+                return Ok(None);
+            };
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!(group_id, first, "querying page of group members");
+            let page = api_client
+                .get_realm_group_members()
+                .realm(&self.config.realm)
+                .group_id(group_id)
+                .set_opt(brief_representation, |r, v| r.brief_representation(v))
+                .first(first)
+                .max(PAGE_MAX)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner();
+            // next page
+            let page_offset = (page.len() == PAGE_MAX as usize).then_some(first + PAGE_MAX);
+            Ok(Some((page, page_offset)))
+        });
+        pages
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_management_permissions(
+        &self,
+        group_id: &str,
+    ) -> Result<ManagementPermissionReference> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying group management permissions");
+        let response = api_client
+            .get_realm_group_management_permissions()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update_group_management_permissions(
+        &self,
+        group_id: &str,
+        permissions: &ManagementPermissionReference,
+    ) -> Result<ManagementPermissionReference> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating group management permissions");
+        let response = api_client
+            .put_realm_group_management_permissions()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .body(permissions.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn group_realm_roles(&self, group_id: &str) -> Result<Vec<RoleRepresentation>> {
         self.refresh_if_necessary().await?;
@@ -136,7 +1095,10 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
 
         tracing::debug!("querying group realm roles");
         let response = api_client
-            .get_realm_group_role_mappings_realm(&self.config.realm, group_id)
+            .get_realm_group_role_mappings_realm()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -147,14 +1109,18 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
     async fn group_add_realm_roles(
         &self,
         group_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
     ) -> Result<()> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
         tracing::debug!("adding realm roles to group");
         api_client
-            .post_realm_group_role_mappings_realm(&self.config.realm, group_id, roles)
+            .post_realm_group_role_mappings_realm()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .body(roles.to_vec())
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -165,17 +1131,186 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakGroupExt for crate:
     async fn group_remove_realm_roles(
         &self,
         group_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
     ) -> Result<()> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
         tracing::debug!("removing realm roles from group");
         api_client
-            .delete_realm_group_role_mappings_realm(&self.config.realm, group_id, roles)
+            .delete_realm_group_role_mappings_realm()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .body(roles.to_vec())
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(())
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying group client roles");
+        let response = api_client
+            .get_realm_group_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .client_id(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_add_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding client roles to group");
+        api_client
+            .post_realm_group_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .client_id(client_uuid)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn group_remove_client_roles(
+        &self,
+        group_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing client roles from group");
+        api_client
+            .delete_realm_group_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .group_id(group_id)
+            .client_id(client_uuid)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+}
+
+/// get a group of a tree by the indices of its ancestors, see [`KeycloakGroupExt::group_tree`]
+fn group_at<'a>(
+    groups: &'a mut [GroupRepresentation],
+    path: &[usize],
+) -> &'a mut GroupRepresentation {
+    let (&first, rest) = path.split_first().expect("BUG: empty group path");
+    rest.iter()
+        .fold(&mut groups[first], |group, &i| &mut group.sub_groups[i])
+}
+
+fn membership_changes(current: &[String], desired: &[&str]) -> MembershipChanges {
+    let current_set: HashSet<&str> = current.iter().map(String::as_str).collect();
+    let desired_set: HashSet<&str> = desired.iter().copied().collect();
+    let mut added_set = HashSet::new();
+    MembershipChanges {
+        added: desired
+            .iter()
+            .filter(|&&id| !current_set.contains(id) && added_set.insert(id))
+            .map(|&id| id.to_string())
+            .collect(),
+        removed: current
+            .iter()
+            .filter(|id| !desired_set.contains(id.as_str()))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn new_group(name: &str, attributes: &HashMap<String, Vec<String>>) -> GroupRepresentation {
+    crate::rest::types::builder::GroupRepresentation::default()
+        .name(name.to_string())
+        .attributes(attributes.clone())
+        .try_into()
+        .expect("BUG: group representation conversion failed")
+}
+
+/// create a group below the given parent, or find the group at `path` if it was created
+/// concurrently
+async fn create_group_at<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    parent_id: Option<&str>,
+    group: &GroupRepresentation,
+    path: &str,
+) -> Result<String> {
+    let result = match parent_id {
+        Some(parent_id) => keycloak.group_create_child(parent_id, group).await,
+        None => keycloak.create_group(group).await,
+    };
+    match result {
+        Err(e) if matches!(e.kind(), ErrorKind::Conflict(_)) => {
+            tracing::debug!(path, "group was created concurrently");
+            let group = keycloak.group_by_path(path).await?;
+            group
+                .id
+                .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))
+        }
+        result => result,
+    }
+}
+
+/// body for updating a group, including its attributes even if there are none left
+fn group_update_body(group: &GroupRepresentation) -> Result<serde_json::Value> {
+    let mut body = serde_json::to_value(group).map_err(crate::error::deserialize)?;
+    if let Some(body) = body.as_object_mut() {
+        body.entry("attributes")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_group_update_body() {
+        let mut group: crate::rest::types::GroupRepresentation =
+            serde_json::from_value(serde_json::json!({ "name": "group" })).unwrap();
+        let body = super::group_update_body(&group).unwrap();
+        assert_eq!(body["attributes"], serde_json::json!({}));
+
+        group.attributes.insert("a".into(), vec!["1".into()]);
+        let body = super::group_update_body(&group).unwrap();
+        assert_eq!(body["attributes"], serde_json::json!({ "a": ["1"] }));
+    }
+
+    #[test]
+    fn test_membership_changes() {
+        let current = ["a".to_string(), "b".to_string(), "c".to_string()];
+        let changes = super::membership_changes(&current, &["c", "d", "a", "d"]);
+        assert_eq!(changes.added, ["d"]);
+        assert_eq!(changes.removed, ["b"]);
+
+        assert!(super::membership_changes(&current, &["a", "b", "c"]).is_empty());
+    }
 }
@@ -0,0 +1,93 @@
+use std::{collections::HashMap, future::Future};
+
+use crate::Error;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// identity-provider-related methods of the keycloak api
+pub trait KeycloakIdentityProviderExt {
+    /// fetch and parse the metadata of an identity provider
+    ///
+    /// `provider_id` is the type of the identity provider (e.g. `saml` or `oidc`), `from_url` the
+    /// url of its saml metadata or openid connect discovery document. the returned config can be
+    /// used as the `config` of a new identity provider.
+    fn identity_provider_import_config(
+        &self,
+        provider_id: &str,
+        from_url: &str,
+    ) -> impl Future<Output = Result<HashMap<String, String>>> + Send;
+
+    /// parse the metadata of an identity provider from an uploaded file
+    ///
+    /// like [`identity_provider_import_config`](Self::identity_provider_import_config), but
+    /// `file` contains the saml metadata or openid connect discovery document itself
+    fn identity_provider_import_config_file(
+        &self,
+        provider_id: &str,
+        file: &[u8],
+    ) -> impl Future<Output = Result<HashMap<String, String>>> + Send;
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakIdentityProviderExt
+    for crate::Keycloak<A>
+{
+    #[tracing::instrument(skip(self))]
+    async fn identity_provider_import_config(
+        &self,
+        provider_id: &str,
+        from_url: &str,
+    ) -> Result<HashMap<String, String>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let mut body = serde_json::Map::new();
+        body.insert("providerId".into(), provider_id.into());
+        body.insert("fromUrl".into(), from_url.into());
+
+        tracing::debug!("importing identity provider config");
+        let response = api_client
+            .post_realm_identity_provider_import_config()
+            .realm(&self.config.realm)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, file))]
+    async fn identity_provider_import_config_file(
+        &self,
+        provider_id: &str,
+        file: &[u8],
+    ) -> Result<HashMap<String, String>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let form = reqwest::multipart::Form::new()
+            .text("providerId", provider_id.to_string())
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(file.to_vec()).file_name("metadata"),
+            );
+
+        tracing::debug!("importing identity provider config from file");
+        // the spec only contains the json variant of this endpoint
+        let response = api_client
+            .client
+            .post(
+                self.config
+                    .admin_url(&["identity-provider", "import-config"])?,
+            )
+            .multipart(form)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+    }
+}
@@ -0,0 +1,338 @@
+use std::future::Future;
+
+use reqwest::StatusCode;
+
+use crate::{
+    error::ResourceType,
+    rest::types::{IdentityProviderRepresentation, UserRepresentation},
+    Error, ErrorKind, KeycloakConfig,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// organization-related methods of the keycloak api
+///
+/// organizations are only available from keycloak 25 on and have to be enabled in the realm.
+/// the openapi spec doesn't contain the organization endpoints, so these methods call them
+/// directly.
+pub trait KeycloakOrganizationExt {
+    /// get all members of an organization
+    fn organization_members(
+        &self,
+        organization_id: &str,
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+
+    /// add an existing user to an organization
+    ///
+    /// fails with [`ErrorKind::Conflict`] if the user already is a member
+    fn add_organization_member(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove a member from an organization
+    ///
+    /// members managed by the organization (e.g. created by one of its identity providers) are
+    /// deleted
+    fn remove_organization_member(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// invite a new user to an organization by email
+    ///
+    /// the user registers using the link in the email and becomes a member afterwards
+    fn invite_organization_user(
+        &self,
+        organization_id: &str,
+        email: &str,
+        first_name: Option<&str>,
+        last_name: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// invite an existing user to an organization by email
+    fn invite_existing_organization_user(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the identity providers linked to an organization
+    fn organization_identity_providers(
+        &self,
+        organization_id: &str,
+    ) -> impl Future<Output = Result<Vec<IdentityProviderRepresentation>>> + Send;
+
+    /// link an identity provider to an organization given its alias
+    ///
+    /// an identity provider can only be linked to a single organization
+    fn link_organization_identity_provider(
+        &self,
+        organization_id: &str,
+        alias: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove the link between an identity provider and an organization
+    fn unlink_organization_identity_provider(
+        &self,
+        organization_id: &str,
+        alias: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakOrganizationExt
+    for crate::Keycloak<A>
+{
+    #[tracing::instrument(skip(self))]
+    async fn organization_members(&self, organization_id: &str) -> Result<Vec<UserRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying organization members");
+        let url = organization_url(&self.config, organization_id, &["members"])?;
+        let response = paginate_api!(|first, max| {
+            json_response::<Vec<UserRepresentation>>(
+                api_client
+                    .client
+                    .get(url.clone())
+                    .query(&[("first", first), ("max", max)])
+                    .send()
+                    .await
+                    .map_err(crate::error::reqwest)?,
+                ResourceType::Organization,
+            )
+            .await?
+        });
+
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_organization_member(&self, organization_id: &str, user_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding organization member");
+        let response = api_client
+            .client
+            .post(organization_url(
+                &self.config,
+                organization_id,
+                &["members"],
+            )?)
+            .json(user_id)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::User)
+            .await
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::User),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_organization_member(&self, organization_id: &str, user_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing organization member");
+        let response = api_client
+            .client
+            .delete(organization_url(
+                &self.config,
+                organization_id,
+                &["members", user_id],
+            )?)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::User).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn invite_organization_user(
+        &self,
+        organization_id: &str,
+        email: &str,
+        first_name: Option<&str>,
+        last_name: Option<&str>,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let mut form = vec![("email", email)];
+        form.extend(first_name.map(|first_name| ("firstName", first_name)));
+        form.extend(last_name.map(|last_name| ("lastName", last_name)));
+
+        tracing::debug!("inviting user to organization");
+        let response = api_client
+            .client
+            .post(organization_url(
+                &self.config,
+                organization_id,
+                &["members", "invite-user"],
+            )?)
+            .form(&form)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::Organization).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn invite_existing_organization_user(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("inviting existing user to organization");
+        let response = api_client
+            .client
+            .post(organization_url(
+                &self.config,
+                organization_id,
+                &["members", "invite-existing-user"],
+            )?)
+            .form(&[("id", user_id)])
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::User).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn organization_identity_providers(
+        &self,
+        organization_id: &str,
+    ) -> Result<Vec<IdentityProviderRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying organization identity providers");
+        let response = api_client
+            .client
+            .get(organization_url(
+                &self.config,
+                organization_id,
+                &["identity-providers"],
+            )?)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        json_response(response, ResourceType::Organization).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn link_organization_identity_provider(
+        &self,
+        organization_id: &str,
+        alias: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("linking identity provider to organization");
+        let response = api_client
+            .client
+            .post(organization_url(
+                &self.config,
+                organization_id,
+                &["identity-providers"],
+            )?)
+            .json(alias)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::IdentityProvider).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn unlink_organization_identity_provider(
+        &self,
+        organization_id: &str,
+        alias: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("unlinking identity provider from organization");
+        let response = api_client
+            .client
+            .delete(organization_url(
+                &self.config,
+                organization_id,
+                &["identity-providers", alias],
+            )?)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        check_response(response, ResourceType::IdentityProvider).await?;
+        Ok(())
+    }
+}
+
+/// url of an organization (or one of its sub-resources given by `segments`)
+fn organization_url(
+    config: &KeycloakConfig,
+    organization_id: &str,
+    segments: &[&str],
+) -> Result<reqwest::Url> {
+    let mut path = vec!["organizations", organization_id];
+    path.extend(segments);
+    config.admin_url(&path)
+}
+
+async fn json_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    not_found: ResourceType,
+) -> Result<T> {
+    let bytes = check_response(response, not_found)
+        .await?
+        .bytes()
+        .await
+        .map_err(crate::error::reqwest)?;
+    serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+}
+
+/// `not_found` is the resource reported if keycloak responds with `404 Not Found`
+async fn check_response(
+    response: reqwest::Response,
+    not_found: ResourceType,
+) -> Result<reqwest::Response> {
+    if !response.status().is_success() {
+        return Err(crate::error::error_response(response)
+            .await
+            .map_status(StatusCode::NOT_FOUND, ErrorKind::NotFound(not_found)));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_organization_url() {
+        let config = crate::KeycloakConfig {
+            base_url: "https://keycloak.example.com/auth/".into(),
+            realm: "test".into(),
+        };
+        let url = super::organization_url(&config, "a/b", &["identity-providers", "my idp"]);
+        assert_eq!(
+            url.unwrap().as_str(),
+            "https://keycloak.example.com/auth/admin/realms/test/organizations/a%2Fb/identity-providers/my%20idp"
+        );
+    }
+}
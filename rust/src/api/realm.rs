@@ -1,18 +1,239 @@
-use std::future::Future;
+use std::{future::Future, time::Duration};
 
+use reqwest::StatusCode;
+
+use super::{KeycloakClientExt, KeycloakRoleExt};
 use crate::{
-    rest::types::{ClientScopeRepresentation, RealmRepresentation},
-    Error,
+    error::ResourceType,
+    rest::{
+        types::{
+            ClientInitialAccessCreatePresentation, ClientInitialAccessPresentation,
+            ClientScopeRepresentation, GlobalRequestResult, RealmRepresentation,
+            RoleRepresentation,
+        },
+        ClientSessionStats, PartialImportRepresentation, PartialImportResults, SyncAction,
+        SynchronizationResult, WhoAmI,
+    },
+    Error, ErrorKind,
 };
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// options for [`clone_realm`](KeycloakRealmExt::clone_realm)
+#[derive(Debug, Clone, Default)]
+pub struct CloneRealmOptions {
+    /// copy the clients (including their roles and authorization settings)
+    pub clients: bool,
+    /// copy the realm roles and groups
+    pub groups_and_roles: bool,
+    /// copy the users, without credentials, role mappings and group memberships
+    pub users: bool,
+}
+
 /// realm-level methods of the keycloak api
 pub trait KeycloakRealmExt {
     fn realm_info(&self) -> impl Future<Output = Result<RealmRepresentation>> + Send;
 
+    /// create a new realm, importing everything contained in the representation (clients, roles,
+    /// users etc.)
+    ///
+    /// unlike the other methods, this doesn't use the configured realm. the authenticated user
+    /// needs the `create-realm` role of the master realm. to manage the new realm, create a
+    /// separate client configured for it. fails with [`ErrorKind::Conflict`] if a realm with the
+    /// same name already exists.
+    fn create_realm(&self, realm: &RealmRepresentation) -> impl Future<Output = Result<()>> + Send;
+
+    /// update the top-level settings of the realm
+    ///
+    /// keycloak ignores nested users, clients, roles etc. in the representation. unset fields
+    /// keep their current values, but it's usually easier to use
+    /// [`modify_realm`](Self::modify_realm) to change a few settings.
+    fn update_realm(&self, realm: &RealmRepresentation) -> impl Future<Output = Result<()>> + Send;
+
+    /// modify the top-level settings of the realm
+    ///
+    /// this fetches the current settings, applies `modify` and writes the result back, so fields
+    /// not touched by `modify` keep their current values. returns the updated settings.
+    fn modify_realm<F>(
+        &self,
+        modify: F,
+    ) -> impl Future<Output = Result<RealmRepresentation>> + Send
+    where
+        F: FnOnce(&mut RealmRepresentation) + Send;
+
+    /// export the realm's configuration, optionally including its clients, groups and roles
+    ///
+    /// users are never exported and keycloak masks secrets (e.g. client secrets) in the export
+    fn realm_partial_export(
+        &self,
+        export_clients: bool,
+        export_groups_and_roles: bool,
+    ) -> impl Future<Output = Result<RealmRepresentation>> + Send;
+
+    /// import users, groups, clients, identity providers and roles into the realm
+    ///
+    /// existing resources are handled according to the import's
+    /// [`if_resource_exists`](PartialImportRepresentation::if_resource_exists) policy. with
+    /// [`ImportPolicy::Fail`](crate::rest::ImportPolicy::Fail), nothing is imported if any
+    /// resource already exists and keycloak responds with `409 Conflict`.
+    fn realm_partial_import(
+        &self,
+        import: &PartialImportRepresentation,
+    ) -> impl Future<Output = Result<PartialImportResults>> + Send;
+
+    /// delete a realm including all of its clients, users etc.
+    ///
+    /// to guard against deleting the wrong realm, `confirmation` has to repeat the realm's name,
+    /// otherwise this fails with [`ErrorKind::NotConfirmed`] without deleting anything. like
+    /// [`create_realm`](Self::create_realm), this doesn't use the configured realm.
+    fn delete_realm(
+        &self,
+        realm: &str,
+        confirmation: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the number of active and offline sessions per client
+    ///
+    /// only clients with at least one session are included
+    fn client_session_stats(&self) -> impl Future<Output = Result<Vec<ClientSessionStats>>> + Send;
+
+    /// remove all user sessions of the realm
+    ///
+    /// keycloak also tells every client with an admin url to invalidate its sessions. the
+    /// result lists the admin urls which were (not) reached.
+    fn realm_logout_all(&self) -> impl Future<Output = Result<GlobalRequestResult>> + Send;
+
+    /// push the realm's not-before policy to every client with an admin url
+    fn realm_push_revocation(&self) -> impl Future<Output = Result<GlobalRequestResult>> + Send;
+
+    /// revoke all tokens of the realm issued before `not_before` (seconds since the unix epoch)
+    ///
+    /// `0` removes the policy. the current policy is the `not_before` of
+    /// [`realm_info`](Self::realm_info). clients with an admin url only learn about the new
+    /// policy when it's pushed using [`realm_push_revocation`](Self::realm_push_revocation).
+    fn set_realm_not_before(&self, not_before: i32) -> impl Future<Output = Result<()>> + Send;
+
+    /// create the realm `target` as a copy of the realm `source`
+    ///
+    /// the source realm is exported using a partial export, so secrets (e.g. client secrets) are
+    /// not copied: new client secrets and realm keys are generated, while secrets of identity
+    /// providers etc. have to be set again. like [`create_realm`](Self::create_realm), this
+    /// doesn't use the configured realm and fails with [`ErrorKind::Conflict`] if `target`
+    /// already exists.
+    fn clone_realm(
+        &self,
+        source: &str,
+        target: &str,
+        options: &CloneRealmOptions,
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get all client scopes configured in the realm
     fn client_scopes(&self) -> impl Future<Output = Result<Vec<ClientScopeRepresentation>>> + Send;
+
+    /// get the authenticated principal and its privileges in the admin api
+    ///
+    /// the principal is taken from the current access token. its management roles are resolved
+    /// using the admin api, which requires the principal to be allowed to view users.
+    fn whoami(&self) -> impl Future<Output = Result<WhoAmI>> + Send;
+
+    /// get the realm's default role (`default-roles-{realm}`)
+    ///
+    /// the roles contained in this composite role are granted to all users of the realm
+    fn default_role(&self) -> impl Future<Output = Result<RoleRepresentation>> + Send;
+
+    /// get the realm and client roles contained in the realm's default role
+    fn default_roles(&self) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// add realm or client roles to the realm's default role
+    fn add_default_roles(
+        &self,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove realm or client roles from the realm's default role
+    fn remove_default_roles(
+        &self,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the client scopes added to new clients as default client scopes
+    fn default_default_client_scopes(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ClientScopeRepresentation>>> + Send;
+
+    /// add a client scope to the default client scopes of new clients
+    ///
+    /// existing clients are not affected
+    fn add_default_default_client_scope(
+        &self,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove a client scope from the default client scopes of new clients
+    ///
+    /// existing clients are not affected
+    fn remove_default_default_client_scope(
+        &self,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the client scopes added to new clients as optional client scopes
+    fn default_optional_client_scopes(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ClientScopeRepresentation>>> + Send;
+
+    /// add a client scope to the optional client scopes of new clients
+    ///
+    /// existing clients are not affected
+    fn add_default_optional_client_scope(
+        &self,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove a client scope from the optional client scopes of new clients
+    ///
+    /// existing clients are not affected
+    fn remove_default_optional_client_scope(
+        &self,
+        scope_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// create an initial access token for dynamic client registration
+    ///
+    /// the token can be used to register `count` clients. it expires after `expiration`, or never
+    /// if `expiration` is `None`. the token is only contained in the response of this method.
+    fn create_initial_access_token(
+        &self,
+        count: u32,
+        expiration: Option<Duration>,
+    ) -> impl Future<Output = Result<ClientInitialAccessPresentation>> + Send;
+
+    /// get all initial access tokens of the realm (without the tokens themselves)
+    fn initial_access_tokens(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ClientInitialAccessPresentation>>> + Send;
+
+    /// delete an initial access token
+    fn delete_initial_access_token(&self, id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// clear the login failures of all users, unlocking all temporarily locked users
+    fn clear_all_login_failures(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// synchronize the users of a user storage provider (e.g. ldap)
+    ///
+    /// `provider_id` is the id of the provider's component. this waits for the synchronization to
+    /// finish.
+    fn user_storage_sync(
+        &self,
+        provider_id: &str,
+        action: SyncAction,
+    ) -> impl Future<Output = Result<SynchronizationResult>> + Send;
+
+    /// remove all users imported from a user storage provider
+    fn user_storage_remove_imported_users(
+        &self,
+        provider_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
 }
 
 impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRealmExt for crate::Keycloak<A> {
@@ -21,12 +242,264 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRealmExt for crate:
         self.refresh_if_necessary().await?;
         let client = self.api_client.read().await;
         let response = client
-            .get_realm(&self.config.realm)
+            .get_realm()
+            .realm(&self.config.realm)
+            .send()
             .await
             .map_err(crate::error::progenitor)?;
         Ok(response.into_inner())
     }
 
+    #[tracing::instrument(skip(self, realm), fields(realm = realm.realm))]
+    async fn create_realm(&self, realm: &RealmRepresentation) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating realm");
+        api_client
+            .post_realms()
+            .body(realm.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Realm),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, realm))]
+    async fn update_realm(&self, realm: &RealmRepresentation) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating realm");
+        api_client
+            .put_realm()
+            .realm(&self.config.realm)
+            .body(realm.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::Realm),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, modify))]
+    async fn modify_realm<F>(&self, modify: F) -> Result<RealmRepresentation>
+    where
+        F: FnOnce(&mut RealmRepresentation) + Send,
+    {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying realm to modify");
+        let mut realm = api_client
+            .get_realm()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        modify(&mut realm);
+
+        tracing::debug!("updating realm");
+        api_client
+            .put_realm()
+            .realm(&self.config.realm)
+            .body(realm.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(realm)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn realm_partial_export(
+        &self,
+        export_clients: bool,
+        export_groups_and_roles: bool,
+    ) -> Result<RealmRepresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("exporting realm");
+        let response = api_client
+            .post_realm_partial_export()
+            .realm(&self.config.realm)
+            .export_clients(export_clients)
+            .export_groups_and_roles(export_groups_and_roles)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, import), fields(policy = ?import.if_resource_exists))]
+    async fn realm_partial_import(
+        &self,
+        import: &PartialImportRepresentation,
+    ) -> Result<PartialImportResults> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("importing into realm");
+        // the spec doesn't specify the body of this endpoint
+        let response = api_client
+            .client
+            .post(self.config.admin_url(&["partialImport"])?)
+            .json(import)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_realm(&self, realm: &str, confirmation: &str) -> Result<()> {
+        if realm != confirmation {
+            return Err(Error::new_kind(ErrorKind::NotConfirmed));
+        }
+
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting realm");
+        api_client
+            .delete_realm()
+            .realm(realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::Realm),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_session_stats(&self) -> Result<Vec<ClientSessionStats>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client session stats");
+        let response = api_client
+            .get_realm_client_session_stats()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        response.into_iter().map(TryFrom::try_from).collect()
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn realm_logout_all(&self) -> Result<GlobalRequestResult> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("logging out all sessions of the realm");
+        let result = api_client
+            .post_realm_logout_all()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn realm_push_revocation(&self) -> Result<GlobalRequestResult> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("pushing revocation policy of the realm");
+        let result = api_client
+            .post_realm_push_revocation()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn set_realm_not_before(&self, not_before: i32) -> Result<()> {
+        self.modify_realm(|realm| realm.not_before = Some(not_before))
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn clone_realm(
+        &self,
+        source: &str,
+        target: &str,
+        options: &CloneRealmOptions,
+    ) -> Result<()> {
+        let mut realm = {
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("exporting source realm");
+            let mut realm = api_client
+                .post_realm_partial_export()
+                .realm(source)
+                .export_clients(options.clients)
+                .export_groups_and_roles(options.groups_and_roles)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)
+                .map_err(|e| {
+                    e.map_status(
+                        StatusCode::NOT_FOUND,
+                        ErrorKind::NotFound(ResourceType::Realm),
+                    )
+                })?
+                .into_inner();
+
+            if options.users {
+                tracing::debug!("querying users of source realm");
+                realm.users = paginate_api!(|first, max| {
+                    api_client
+                        .get_realm_users()
+                        .realm(source)
+                        .first(first)
+                        .max(max)
+                        .send()
+                        .await
+                        .map_err(crate::error::progenitor)?
+                        .into_inner()
+                });
+            }
+            realm
+        };
+
+        let mut value = serde_json::to_value(&realm).map_err(crate::error::deserialize)?;
+        rewrite_realm_export(&mut value, target);
+        realm = serde_json::from_value(value).map_err(crate::error::deserialize)?;
+
+        self.create_realm(&realm).await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn client_scopes(&self) -> Result<Vec<ClientScopeRepresentation>> {
         self.refresh_if_necessary().await?;
@@ -34,10 +507,413 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRealmExt for crate:
 
         tracing::debug!("querying all client scopes");
         let response = api_client
-            .get_realm_client_scopes(&self.config.realm)
+            .get_realm_client_scopes()
+            .realm(&self.config.realm)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
+
+    #[tracing::instrument(skip(self))]
+    async fn whoami(&self) -> Result<WhoAmI> {
+        self.refresh_if_necessary().await?;
+        let claims = {
+            let auth = self.auth.read().await;
+            let Some(access_token) = auth.access_token() else {
+                return Err(Error::new_kind(ErrorKind::MissingAccessToken));
+            };
+            crate::auth::decode_token_claims(access_token)?
+        };
+
+        let Some(user_id) = claims.sub.as_deref() else {
+            tracing::debug!("access token has no subject, skipping management roles");
+            return Ok(WhoAmI {
+                claims,
+                management_roles: None,
+            });
+        };
+        let management_client_id = if self.config.realm == "master" {
+            "master-realm"
+        } else {
+            "realm-management"
+        };
+        let management_roles = async {
+            let client = self.client_by_id(management_client_id).await?;
+            let Some(client_uuid) = client.id else {
+                return Err(Error::new_kind(ErrorKind::MissingId));
+            };
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("querying effective management roles");
+            let roles = api_client
+                .get_realm_user_role_mappings_clients_client_id_composite()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .client_id(&client_uuid)
+                .brief_representation(true)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner();
+            Ok(roles)
+        }
+        .await;
+        let management_roles = match management_roles {
+            Ok(roles) => Some(roles),
+            Err(e) => {
+                tracing::debug!("failed to resolve management roles: {e}");
+                None
+            }
+        };
+
+        Ok(WhoAmI {
+            claims,
+            management_roles,
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn default_role(&self) -> Result<RoleRepresentation> {
+        self.realm_info()
+            .await?
+            .default_role
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingField("defaultRole".into())))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn default_roles(&self) -> Result<Vec<RoleRepresentation>> {
+        let default_role = self.default_role().await?;
+        let role_id = default_role
+            .id
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+        self.role_composites_by_id(&role_id).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_default_roles(&self, roles: &[RoleRepresentation]) -> Result<()> {
+        let default_role = self.default_role().await?;
+        let role_id = default_role
+            .id
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+        self.add_role_composites_by_id(&role_id, roles).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_default_roles(&self, roles: &[RoleRepresentation]) -> Result<()> {
+        let default_role = self.default_role().await?;
+        let role_id = default_role
+            .id
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+        self.remove_role_composites_by_id(&role_id, roles).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn default_default_client_scopes(&self) -> Result<Vec<ClientScopeRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying realm default client scopes");
+        let scopes = api_client
+            .get_realm_default_default_client_scopes()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(scopes)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_default_default_client_scope(&self, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding realm default client scope");
+        api_client
+            .put_realm_default_default_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_default_default_client_scope(&self, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing realm default client scope");
+        api_client
+            .delete_realm_default_default_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn default_optional_client_scopes(&self) -> Result<Vec<ClientScopeRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying realm optional client scopes");
+        let scopes = api_client
+            .get_realm_default_optional_client_scopes()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(scopes)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_default_optional_client_scope(&self, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding realm optional client scope");
+        api_client
+            .put_realm_default_optional_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_default_optional_client_scope(&self, scope_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing realm optional client scope");
+        api_client
+            .delete_realm_default_optional_client_scopes_client_scope_id()
+            .realm(&self.config.realm)
+            .client_scope_id(scope_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn create_initial_access_token(
+        &self,
+        count: u32,
+        expiration: Option<Duration>,
+    ) -> Result<ClientInitialAccessPresentation> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        // an expiration of 0 means the token never expires
+        let expiration = expiration.map_or(0, |e| e.as_secs().try_into().unwrap_or(i32::MAX));
+        let body = ClientInitialAccessCreatePresentation {
+            count: Some(count.try_into().unwrap_or(i32::MAX)),
+            expiration: Some(expiration),
+        };
+        tracing::debug!("creating initial access token");
+        let token = api_client
+            .post_realm_clients_initial_access()
+            .realm(&self.config.realm)
+            .body(body)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(token)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn initial_access_tokens(&self) -> Result<Vec<ClientInitialAccessPresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying initial access tokens");
+        let tokens = api_client
+            .get_realm_clients_initial_access()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(tokens)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_initial_access_token(&self, id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting initial access token");
+        api_client
+            .delete_realm_clients_initial_access_id()
+            .realm(&self.config.realm)
+            .id(id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn clear_all_login_failures(&self) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("clearing login failures of all users");
+        api_client
+            .delete_realm_attack_detection_brute_force_users()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_storage_sync(
+        &self,
+        provider_id: &str,
+        action: SyncAction,
+    ) -> Result<SynchronizationResult> {
+        self.refresh_if_necessary().await?;
+        let client = self.api_client.read().await;
+        let client = &client.client;
+
+        tracing::debug!("synchronizing user storage");
+        // the user storage endpoints are not part of the openapi spec
+        let response = client
+            .post(
+                self.config
+                    .admin_url(&["user-storage", provider_id, "sync"])?,
+            )
+            .query(&[("action", action.as_query())])
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        let data = serde_json::from_slice(&bytes).map_err(crate::error::deserialize)?;
+        Ok(data)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_storage_remove_imported_users(&self, provider_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let client = self.api_client.read().await;
+        let client = &client.client;
+
+        tracing::debug!("removing users imported from user storage");
+        // the user storage endpoints are not part of the openapi spec
+        let response = client
+            .post(
+                self.config
+                    .admin_url(&["user-storage", provider_id, "remove-imported-users"])?,
+            )
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        Ok(())
+    }
+}
+
+/// prepare the partial export of a realm for importing it as the realm `target`
+///
+/// ids are removed, since they have to be unique across all realms. key providers and client
+/// secrets are removed as well, since the export only contains masked values.
+fn rewrite_realm_export(export: &mut serde_json::Value, target: &str) {
+    fn strip_ids(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for field in ["id", "containerId", "parentId"] {
+                    object.remove(field);
+                }
+                for (key, value) in object.iter_mut() {
+                    // user-defined maps, which may contain an "id" key
+                    if key != "attributes" && key != "config" {
+                        strip_ids(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip_ids),
+            _ => {}
+        }
+    }
+
+    strip_ids(export);
+    let Some(realm) = export.as_object_mut() else {
+        return;
+    };
+    realm.insert("realm".into(), target.into());
+    if let Some(components) = realm
+        .get_mut("components")
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        components.remove("org.keycloak.keys.KeyProvider");
+    }
+    if let Some(clients) = realm
+        .get_mut("clients")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for client in clients
+            .iter_mut()
+            .filter_map(serde_json::Value::as_object_mut)
+        {
+            client.remove("secret");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_rewrite_realm_export() {
+        let mut export = serde_json::json!({
+            "id": "source-id",
+            "realm": "source",
+            "attributes": { "id": "kept" },
+            "clients": [
+                { "id": "c1", "clientId": "app", "secret": "**********" },
+            ],
+            "roles": {
+                "realm": [{ "id": "r1", "name": "admin", "containerId": "source-id" }],
+            },
+            "components": {
+                "org.keycloak.keys.KeyProvider": [{ "id": "k1", "name": "rsa" }],
+                "org.keycloak.userprofile.UserProfileProvider": [
+                    { "id": "p1", "parentId": "source-id", "config": { "id": ["kept"] } },
+                ],
+            },
+        });
+        super::rewrite_realm_export(&mut export, "target");
+        assert_eq!(
+            export,
+            serde_json::json!({
+                "realm": "target",
+                "attributes": { "id": "kept" },
+                "clients": [{ "clientId": "app" }],
+                "roles": { "realm": [{ "name": "admin" }] },
+                "components": {
+                    "org.keycloak.userprofile.UserProfileProvider": [
+                        { "config": { "id": ["kept"] } },
+                    ],
+                },
+            })
+        );
+    }
 }
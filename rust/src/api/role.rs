@@ -1,13 +1,56 @@
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 
-use super::KeycloakGroupExt;
+use reqwest::StatusCode;
+
+use super::{Ensured, KeycloakClientExt, KeycloakGroupExt};
 use crate::{
+    error::ResourceType,
     rest::types::{GroupRepresentation, RoleRepresentation, UserRepresentation},
-    Error,
+    util::RequestBuilderExt,
+    Error, ErrorKind,
 };
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// which users count as members of a role, see [`KeycloakRoleExt::role_members`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoleMembership {
+    /// only users the role is assigned to directly
+    #[default]
+    Direct,
+    /// also members of groups with the role, including members of their subgroups (which inherit
+    /// the roles of their parents)
+    Groups,
+    /// like [`Groups`](RoleMembership::Groups), but also users and groups with a composite role
+    /// containing the role (directly or through other composite roles)
+    ///
+    /// keycloak can't look up composite roles by their children, so this queries the composites
+    /// of every realm and client role
+    Composites,
+}
+
+impl From<bool> for RoleMembership {
+    /// the former `include_indirect` flag of [`KeycloakRoleExt::users_in_role`]
+    fn from(include_indirect: bool) -> Self {
+        if include_indirect {
+            Self::Groups
+        } else {
+            Self::Direct
+        }
+    }
+}
+
+/// a role found by [`search_roles`](KeycloakRoleExt::search_roles)
+#[derive(Debug, Clone)]
+pub struct RoleMatch {
+    pub role: RoleRepresentation,
+    /// client id (not the uuid) of client roles, `None` for realm roles
+    pub client_id: Option<String>,
+    /// uuid of the client of client roles
+    pub client_uuid: Option<String>,
+}
+
 /// role-related methods of the keycloak api
 pub trait KeycloakRoleExt {
     /// get a single role matching the given name
@@ -16,9 +59,101 @@ pub trait KeycloakRoleExt {
         role_name: &str,
     ) -> impl Future<Output = Result<RoleRepresentation>> + Send;
 
+    /// get all realm roles
+    ///
+    /// if a `search` string is given, only roles whose name contains it are returned
+    fn realm_roles(
+        &self,
+        brief_representation: Option<bool>,
+        search: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// get all roles of the client with the given uuid
+    ///
+    /// if a `search` string is given, only roles whose name contains it are returned
+    fn client_roles(
+        &self,
+        client_uuid: &str,
+        brief_representation: Option<bool>,
+        search: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// search roles whose name contains the given string
+    ///
+    /// if `include_client_roles` is `true`, the roles of all clients are searched as well, which
+    /// takes one request per client
+    fn search_roles(
+        &self,
+        search: &str,
+        include_client_roles: bool,
+    ) -> impl Future<Output = Result<Vec<RoleMatch>>> + Send;
+
+    /// create a realm role or update it if it already exists
+    ///
+    /// the description of existing roles is only changed if a `description` is given. the role
+    /// contains exactly the given `composites` afterwards (which need to have ids), so pass an
+    /// empty slice for simple roles.
+    fn ensure_realm_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        composites: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<Ensured>> + Send;
+
+    /// create a role of the client with the given uuid or update it if it already exists
+    ///
+    /// see [`ensure_realm_role`](KeycloakRoleExt::ensure_realm_role)
+    fn ensure_client_role(
+        &self,
+        client_uuid: &str,
+        name: &str,
+        description: Option<&str>,
+        composites: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<Ensured>> + Send;
+
     /// get a single role given its id
     fn role_by_id(&self, role_id: &str) -> impl Future<Output = Result<RoleRepresentation>> + Send;
 
+    /// update a role given its id
+    ///
+    /// keycloak replaces the description and attributes of the role with the given ones
+    fn update_role_by_id(
+        &self,
+        role_id: &str,
+        role: &RoleRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the roles directly contained in a composite role
+    fn role_composites_by_id(
+        &self,
+        role_id: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// get all roles granted by a composite role
+    ///
+    /// composites of composite roles are expanded recursively, every role is returned once (cycles
+    /// between composite roles are allowed by keycloak). the role itself is not included.
+    fn role_effective_composites(
+        &self,
+        role_id: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// add realm or client roles to a composite role
+    ///
+    /// this makes the role a composite role if it isn't already
+    fn add_role_composites_by_id(
+        &self,
+        role_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove realm or client roles from a composite role
+    fn remove_role_composites_by_id(
+        &self,
+        role_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
     /// get direct member groups of a role
     ///
     /// if a `client_id` is specified, this queries a client role
@@ -28,25 +163,50 @@ pub trait KeycloakRoleExt {
         role_name: &str,
     ) -> impl Future<Output = Result<Vec<GroupRepresentation>>> + Send;
 
+    /// get all members of a role, see [`role_members`](KeycloakRoleExt::role_members)
+    fn role_members_by_id(
+        &self,
+        role_id: &str,
+        membership: RoleMembership,
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+
+    /// get all members of a role
+    ///
+    /// `membership` selects which users count as members, see [`RoleMembership`]. every user is
+    /// returned only once, even if they have the role in multiple ways.
+    /// if a `client_id` is specified, this queries a client role
+    fn role_members(
+        &self,
+        client_id: Option<&str>,
+        role_name: &str,
+        membership: RoleMembership,
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+
     /// get all members of a role
     ///
     /// if `include_indirect` is `true`, members of groups with this role are returned as well
+    #[deprecated(note = "use `role_members_by_id` instead")]
     fn users_in_role_by_id(
         &self,
         role_id: &str,
         include_indirect: bool,
-    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send {
+        self.role_members_by_id(role_id, RoleMembership::from(include_indirect))
+    }
 
     /// get all members of a role
     ///
     /// if `include_indirect` is `true`, members of groups with this role are returned as well
     /// if a `client_id` is specified, this queries a client role
+    #[deprecated(note = "use `role_members` instead")]
     fn users_in_role(
         &self,
         client_id: Option<&str>,
         role_name: &str,
         include_indirect: bool,
-    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send {
+        self.role_members(client_id, role_name, RoleMembership::from(include_indirect))
+    }
 }
 
 impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::Keycloak<A> {
@@ -57,13 +217,127 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::
 
         tracing::debug!("querying role by name");
         let response = api_client
-            .get_realm_role_by_name(&self.config.realm, role_name)
+            .get_realm_role_by_name()
+            .realm(&self.config.realm)
+            .role_name(role_name)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn realm_roles(
+        &self,
+        brief_representation: Option<bool>,
+        search: Option<&str>,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying realm roles");
+        let roles = paginate_api!(|first, max| {
+            api_client
+                .get_realm_roles()
+                .realm(&self.config.realm)
+                .set_opt(brief_representation, |r, v| r.brief_representation(v))
+                .set_opt(search, |r, v| r.search(v))
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(roles)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn client_roles(
+        &self,
+        client_uuid: &str,
+        brief_representation: Option<bool>,
+        search: Option<&str>,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying client roles");
+        let roles = paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_roles()
+                .realm(&self.config.realm)
+                .client_uuid(client_uuid)
+                .set_opt(brief_representation, |r, v| r.brief_representation(v))
+                .set_opt(search, |r, v| r.search(v))
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(roles)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search_roles(
+        &self,
+        search: &str,
+        include_client_roles: bool,
+    ) -> Result<Vec<RoleMatch>> {
+        let mut matches: Vec<_> = self
+            .realm_roles(Some(true), Some(search))
+            .await?
+            .into_iter()
+            .map(|role| RoleMatch {
+                role,
+                client_id: None,
+                client_uuid: None,
+            })
+            .collect();
+        if !include_client_roles {
+            return Ok(matches);
+        }
+
+        for client in self.clients().await? {
+            let Some(client_uuid) = client.id else {
+                continue;
+            };
+            let roles = self
+                .client_roles(&client_uuid, Some(true), Some(search))
+                .await?;
+            matches.extend(roles.into_iter().map(|role| RoleMatch {
+                role,
+                client_id: client.client_id.clone(),
+                client_uuid: Some(client_uuid.clone()),
+            }));
+        }
+        Ok(matches)
+    }
+
+    #[tracing::instrument(skip(self, composites))]
+    async fn ensure_realm_role(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        composites: &[RoleRepresentation],
+    ) -> Result<Ensured> {
+        ensure_role(self, None, name, description, composites).await
+    }
+
+    #[tracing::instrument(skip(self, composites))]
+    async fn ensure_client_role(
+        &self,
+        client_uuid: &str,
+        name: &str,
+        description: Option<&str>,
+        composites: &[RoleRepresentation],
+    ) -> Result<Ensured> {
+        ensure_role(self, Some(client_uuid), name, description, composites).await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn role_by_id(&self, role_id: &str) -> Result<RoleRepresentation> {
         self.refresh_if_necessary().await?;
@@ -71,13 +345,118 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::
 
         tracing::debug!("querying role by id");
         let response = api_client
-            .get_realm_role_by_id(&self.config.realm, role_id)
+            .get_realm_role_by_id()
+            .realm(&self.config.realm)
+            .role_id(role_id)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self, role))]
+    async fn update_role_by_id(&self, role_id: &str, role: &RoleRepresentation) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating role by id");
+        api_client
+            .put_realm_role_by_id()
+            .realm(&self.config.realm)
+            .role_id(role_id)
+            .body(role.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn role_composites_by_id(&self, role_id: &str) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying composite roles");
+        let roles = paginate_api!(|first, max| {
+            api_client
+                .get_realm_role_by_id_composites()
+                .realm(&self.config.realm)
+                .role_id(role_id)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        });
+        Ok(roles)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn role_effective_composites(&self, role_id: &str) -> Result<Vec<RoleRepresentation>> {
+        let mut visited = HashSet::from([role_id.to_string()]);
+        let mut pending = vec![role_id.to_string()];
+        let mut roles = Vec::new();
+        while let Some(id) = pending.pop() {
+            for role in self.role_composites_by_id(&id).await? {
+                let Some(child_id) = &role.id else {
+                    continue;
+                };
+                if !visited.insert(child_id.clone()) {
+                    continue;
+                }
+                if role.composite == Some(true) {
+                    pending.push(child_id.clone());
+                }
+                roles.push(role);
+            }
+        }
+        Ok(roles)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn add_role_composites_by_id(
+        &self,
+        role_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding composite roles");
+        api_client
+            .post_realm_role_by_id_composites()
+            .realm(&self.config.realm)
+            .role_id(role_id)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn remove_role_composites_by_id(
+        &self,
+        role_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing composite roles");
+        api_client
+            .delete_realm_role_by_id_composites()
+            .realm(&self.config.realm)
+            .role_id(role_id)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     async fn groups_in_role(
         &self,
@@ -91,14 +470,14 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::
         let groups = if let Some(client_id) = client_id {
             paginate_api!(|first, max| {
                 api_client
-                    .get_realm_client_role_by_name_groups(
-                        &self.config.realm,
-                        client_id,
-                        role_name,
-                        Some(true),
-                        Some(first),
-                        Some(max),
-                    )
+                    .get_realm_client_role_by_name_groups()
+                    .realm(&self.config.realm)
+                    .client_uuid(client_id)
+                    .role_name(role_name)
+                    .brief_representation(true)
+                    .first(first)
+                    .max(max)
+                    .send()
                     .await
                     .map_err(crate::error::progenitor)?
                     .into_inner()
@@ -106,13 +485,13 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::
         } else {
             paginate_api!(|first, max| {
                 api_client
-                    .get_realm_role_by_name_groups(
-                        &self.config.realm,
-                        role_name,
-                        Some(true),
-                        Some(first),
-                        Some(max),
-                    )
+                    .get_realm_role_by_name_groups()
+                    .realm(&self.config.realm)
+                    .role_name(role_name)
+                    .brief_representation(true)
+                    .first(first)
+                    .max(max)
+                    .send()
                     .await
                     .map_err(crate::error::progenitor)?
                     .into_inner()
@@ -122,80 +501,310 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakRoleExt for crate::
     }
 
     #[tracing::instrument(skip(self))]
-    async fn users_in_role_by_id(
+    async fn role_members_by_id(
         &self,
         role_id: &str,
-        include_indirect: bool,
+        membership: RoleMembership,
     ) -> Result<Vec<UserRepresentation>> {
         let role = self.role_by_id(role_id).await?;
-
-        if role.client_role == Some(true) {
-            self.users_in_role(
-                Some(&role.container_id.unwrap()),
-                &role.name.unwrap(),
-                include_indirect,
-            )
+        let (client_id, role_name) = role_key(&role)?;
+        self.role_members(client_id.as_deref(), &role_name, membership)
             .await
-        } else {
-            self.users_in_role(None, &role.name.unwrap(), include_indirect)
-                .await
-        }
     }
 
     #[tracing::instrument(skip(self))]
-    async fn users_in_role(
+    async fn role_members(
         &self,
         client_id: Option<&str>,
         role_name: &str,
-        include_indirect: bool,
+        membership: RoleMembership,
     ) -> Result<Vec<UserRepresentation>> {
-        self.refresh_if_necessary().await?;
-        let api_client = self.api_client.read().await;
+        let mut roles = vec![(client_id.map(String::from), role_name.to_string())];
+        if membership == RoleMembership::Composites {
+            let role = role_by_key(self, client_id, role_name).await?;
+            let role_id = role
+                .id
+                .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+            for parent in composite_parents(self, &role_id).await? {
+                roles.push(role_key(&parent)?);
+            }
+        }
 
-        tracing::debug!("querying users with role");
-        let mut users = if let Some(client_id) = client_id {
-            paginate_api!(|first, max| {
-                api_client
-                    .get_realm_client_role_by_name_users(
-                        &self.config.realm,
-                        client_id,
-                        role_name,
-                        None,
-                        Some(first),
-                        Some(max),
-                    )
-                    .await
-                    .map_err(crate::error::progenitor)?
-                    .into_inner()
-            })
-        } else {
-            paginate_api!(|first, max| {
-                api_client
-                    .get_realm_role_by_name_users(
-                        &self.config.realm,
-                        role_name,
-                        None,
-                        Some(first),
-                        Some(max),
-                    )
-                    .await
-                    .map_err(crate::error::progenitor)?
-                    .into_inner()
-            })
+        let mut users = Vec::new();
+        let mut seen_users = HashSet::new();
+        let mut seen_groups = HashSet::new();
+        let mut add_users = |new_users: Vec<UserRepresentation>| {
+            for user in new_users {
+                // deduplicate users who have the role in multiple ways
+                let new = match &user.id {
+                    Some(id) => seen_users.insert(id.clone()),
+                    None => true,
+                };
+                if new {
+                    users.push(user);
+                }
+            }
         };
+        for (client_id, role_name) in &roles {
+            add_users(direct_role_users(self, client_id.as_deref(), role_name).await?);
+            if membership == RoleMembership::Direct {
+                continue;
+            }
 
-        if include_indirect {
-            let groups = self.groups_in_role(client_id, role_name).await?;
-            for group in &groups {
-                let mut group_users = self
-                    .group_users(group.id.as_ref().unwrap(), Some(true))
-                    .await?;
-                // deduplicate users who are members of multiple roles
-                group_users.retain(|group_user| !users.iter().any(|u| u.id == group_user.id));
-                users.extend(group_users);
+            let mut pending: Vec<String> = self
+                .groups_in_role(client_id.as_deref(), role_name)
+                .await?
+                .into_iter()
+                .filter_map(|group| group.id)
+                .collect();
+            while let Some(group_id) = pending.pop() {
+                // groups can't form cycles, but a group may be reachable through several roles
+                if !seen_groups.insert(group_id.clone()) {
+                    continue;
+                }
+                add_users(self.group_members(&group_id, Some(true)).await?);
+                let children = self.all_group_children(&group_id).await?;
+                pending.extend(children.into_iter().filter_map(|group| group.id));
             }
         }
 
         Ok(users)
     }
 }
+
+/// get the client uuid (for client roles) and name of a role
+fn role_key(role: &RoleRepresentation) -> Result<(Option<String>, String)> {
+    let name = role
+        .name
+        .clone()
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingField("name".into())))?;
+    if role.client_role != Some(true) {
+        return Ok((None, name));
+    }
+    let client_id = role
+        .container_id
+        .clone()
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingField("containerId".into())))?;
+    Ok((Some(client_id), name))
+}
+
+async fn role_by_key<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    client_id: Option<&str>,
+    role_name: &str,
+) -> Result<RoleRepresentation> {
+    let Some(client_id) = client_id else {
+        return keycloak.role_by_name(role_name).await;
+    };
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!(client_id, role_name, "querying client role by name");
+    let role = api_client
+        .get_realm_client_role_by_name()
+        .realm(&keycloak.config.realm)
+        .client_uuid(client_id)
+        .role_name(role_name)
+        .send()
+        .await
+        .map_err(crate::error::progenitor)?
+        .into_inner();
+    Ok(role)
+}
+
+async fn direct_role_users<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    client_id: Option<&str>,
+    role_name: &str,
+) -> Result<Vec<UserRepresentation>> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!(client_id, role_name, "querying users with role");
+    let users = if let Some(client_id) = client_id {
+        paginate_api!(|first, max| {
+            api_client
+                .get_realm_client_role_by_name_users()
+                .realm(&keycloak.config.realm)
+                .client_uuid(client_id)
+                .role_name(role_name)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        })
+    } else {
+        paginate_api!(|first, max| {
+            api_client
+                .get_realm_role_by_name_users()
+                .realm(&keycloak.config.realm)
+                .role_name(role_name)
+                .first(first)
+                .max(max)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        })
+    };
+    Ok(users)
+}
+
+async fn ensure_role<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    client_id: Option<&str>,
+    name: &str,
+    description: Option<&str>,
+    composites: &[RoleRepresentation],
+) -> Result<Ensured> {
+    let (role, created) = match role_by_key(keycloak, client_id, name).await {
+        Ok(role) => (role, false),
+        Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => {
+            match create_role(keycloak, client_id, name, description).await {
+                Ok(()) => {}
+                // created concurrently, update that role instead
+                Err(e) if matches!(e.kind(), ErrorKind::Conflict(_)) => {
+                    tracing::debug!("role was created concurrently");
+                }
+                Err(e) => return Err(e),
+            }
+            (role_by_key(keycloak, client_id, name).await?, true)
+        }
+        Err(e) => return Err(e),
+    };
+    let role_id = role
+        .id
+        .clone()
+        .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+
+    if description.is_some_and(|description| role.description.as_deref() != Some(description)) {
+        let mut role = role.clone();
+        role.description = description.map(Into::into);
+        keycloak.update_role_by_id(&role_id, &role).await?;
+    }
+
+    let current = if role.composite == Some(true) {
+        keycloak.role_composites_by_id(&role_id).await?
+    } else {
+        Vec::new()
+    };
+    let mut desired_ids = HashSet::new();
+    for composite in composites {
+        let id = composite
+            .id
+            .as_deref()
+            .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?;
+        desired_ids.insert(id);
+    }
+    let current_ids: HashSet<_> = current.iter().filter_map(|r| r.id.as_deref()).collect();
+    let missing: Vec<_> = composites
+        .iter()
+        .filter(|r| r.id.as_deref().is_some_and(|id| !current_ids.contains(id)))
+        .cloned()
+        .collect();
+    let extra: Vec<_> = current
+        .iter()
+        .filter(|r| r.id.as_deref().is_some_and(|id| !desired_ids.contains(id)))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        keycloak
+            .add_role_composites_by_id(&role_id, &missing)
+            .await?;
+    }
+    if !extra.is_empty() {
+        keycloak
+            .remove_role_composites_by_id(&role_id, &extra)
+            .await?;
+    }
+
+    Ok(if created {
+        Ensured::Created(role_id)
+    } else {
+        Ensured::Updated(role_id)
+    })
+}
+
+async fn create_role<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    client_id: Option<&str>,
+    name: &str,
+    description: Option<&str>,
+) -> Result<()> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    tracing::debug!(client_id, name, "creating role");
+    let body = |role: crate::rest::types::builder::RoleRepresentation| {
+        role.name(name.to_string())
+            .description(description.map(String::from))
+    };
+    let result = if let Some(client_id) = client_id {
+        api_client
+            .post_realm_client_roles()
+            .realm(&keycloak.config.realm)
+            .client_uuid(client_id)
+            .body_map(body)
+            .send()
+            .await
+    } else {
+        api_client
+            .post_realm_roles()
+            .realm(&keycloak.config.realm)
+            .body_map(body)
+            .send()
+            .await
+    };
+    result.map_err(crate::error::progenitor).map_err(|e| {
+        e.map_status(
+            StatusCode::CONFLICT,
+            ErrorKind::Conflict(ResourceType::Role),
+        )
+    })?;
+    Ok(())
+}
+
+/// find all composite roles containing the given role, directly or through other composites
+async fn composite_parents<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    role_id: &str,
+) -> Result<Vec<RoleRepresentation>> {
+    let mut roles = keycloak.realm_roles(None, None).await?;
+    for client in keycloak.clients().await? {
+        if let Some(uuid) = &client.id {
+            roles.extend(keycloak.client_roles(uuid, None, None).await?);
+        }
+    }
+
+    let mut parents: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, role) in roles.iter().enumerate() {
+        let (Some(true), Some(id)) = (role.composite, &role.id) else {
+            continue;
+        };
+        for child in keycloak.role_composites_by_id(id).await? {
+            if let Some(child_id) = child.id {
+                parents.entry(child_id).or_default().push(index);
+            }
+        }
+    }
+
+    // composite roles may contain each other, so keep track of the roles already visited
+    let mut visited = HashSet::from([role_id.to_string()]);
+    let mut pending = vec![role_id.to_string()];
+    let mut found = Vec::new();
+    while let Some(id) = pending.pop() {
+        for &index in parents.get(&id).map(Vec::as_slice).unwrap_or_default() {
+            let Some(parent_id) = &roles[index].id else {
+                continue;
+            };
+            if visited.insert(parent_id.clone()) {
+                pending.push(parent_id.clone());
+                found.push(index);
+            }
+        }
+    }
+    let mut roles: Vec<_> = roles.into_iter().map(Some).collect();
+    Ok(found.into_iter().filter_map(|i| roles[i].take()).collect())
+}
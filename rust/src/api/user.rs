@@ -1,23 +1,537 @@
+use std::collections::HashMap;
 use std::future::Future;
+use std::time::Duration;
 
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+
+use super::{Ensured, KeycloakClientExt, KeycloakGroupExt, KeycloakRealmExt, KeycloakRoleExt};
 use crate::{
-    rest::types::{RoleRepresentation, UserRepresentation},
+    error::ResourceType,
+    rest::{
+        builder::{GetRealmUsers, GetRealmUsersCount},
+        types::{
+            CredentialRepresentation, FederatedIdentityRepresentation, MappingsRepresentation,
+            RoleRepresentation, UpConfig, UserProfileMetadata, UserRepresentation,
+            UserSessionRepresentation,
+        },
+        BruteForceStatus, ImportAction, ImportPolicy, PartialImportRepresentation,
+        PartialImportResult, PartialImportResults,
+    },
+    util::{merge_representation, RequestBuilderExt},
     Error, ErrorKind,
 };
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// filters for [`search_users`](KeycloakUserExt::search_users)
+///
+/// all filters are combined, so only users matching every filter are returned
+#[derive(Debug, Clone, Default)]
+pub struct UserQuery {
+    search: Option<String>,
+    username: Option<String>,
+    email: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    enabled: Option<bool>,
+    email_verified: Option<bool>,
+    exact: Option<bool>,
+    attributes: Vec<(String, String)>,
+    idp_alias: Option<String>,
+    idp_user_id: Option<String>,
+    brief: Option<bool>,
+}
+
+impl UserQuery {
+    /// no filters, matches all users
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// search string matched against username, first and last name and email
+    pub fn search(mut self, search: &str) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    pub fn first_name(mut self, first_name: &str) -> Self {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    pub fn last_name(mut self, last_name: &str) -> Self {
+        self.last_name = Some(last_name.into());
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    pub fn email_verified(mut self, email_verified: bool) -> Self {
+        self.email_verified = Some(email_verified);
+        self
+    }
+
+    /// match username, email, first and last name exactly instead of as substrings
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = Some(exact);
+        self
+    }
+
+    /// only return users with the given attribute value
+    ///
+    /// keycloak separates multiple attribute filters using spaces, so neither key nor value may
+    /// contain spaces or colons
+    pub fn attribute(mut self, key: &str, value: &str) -> Self {
+        self.attributes.push((key.into(), value.into()));
+        self
+    }
+
+    /// only return users linked to the given identity provider
+    pub fn idp_alias(mut self, idp_alias: &str) -> Self {
+        self.idp_alias = Some(idp_alias.into());
+        self
+    }
+
+    /// only return users with the given user id at the identity provider
+    pub fn idp_user_id(mut self, idp_user_id: &str) -> Self {
+        self.idp_user_id = Some(idp_user_id.into());
+        self
+    }
+
+    /// only return the basic user fields (no attributes etc.)
+    pub fn brief(mut self, brief: bool) -> Self {
+        self.brief = Some(brief);
+        self
+    }
+
+    /// the `q` parameter for the attribute filters
+    fn attribute_query(&self) -> Option<String> {
+        if self.attributes.is_empty() {
+            return None;
+        }
+        let query = self
+            .attributes
+            .iter()
+            .map(|(key, value)| format!("{key}:{value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(query)
+    }
+
+    fn apply<'a>(&self, request: GetRealmUsers<'a>) -> GetRealmUsers<'a> {
+        request
+            .set_opt(self.search.clone(), |r, v| r.search(v))
+            .set_opt(self.username.clone(), |r, v| r.username(v))
+            .set_opt(self.email.clone(), |r, v| r.email(v))
+            .set_opt(self.first_name.clone(), |r, v| r.first_name(v))
+            .set_opt(self.last_name.clone(), |r, v| r.last_name(v))
+            .set_opt(self.enabled, |r, v| r.enabled(v))
+            .set_opt(self.email_verified, |r, v| r.email_verified(v))
+            .set_opt(self.exact, |r, v| r.exact(v))
+            .set_opt(self.attribute_query(), |r, v| r.q(v))
+            .set_opt(self.idp_alias.clone(), |r, v| r.idp_alias(v))
+            .set_opt(self.idp_user_id.clone(), |r, v| r.idp_user_id(v))
+            .set_opt(self.brief, |r, v| r.brief_representation(v))
+    }
+
+    fn apply_count<'a>(&self, request: GetRealmUsersCount<'a>) -> GetRealmUsersCount<'a> {
+        request
+            .set_opt(self.search.clone(), |r, v| r.search(v))
+            .set_opt(self.username.clone(), |r, v| r.username(v))
+            .set_opt(self.email.clone(), |r, v| r.email(v))
+            .set_opt(self.first_name.clone(), |r, v| r.first_name(v))
+            .set_opt(self.last_name.clone(), |r, v| r.last_name(v))
+            .set_opt(self.enabled, |r, v| r.enabled(v))
+            .set_opt(self.email_verified, |r, v| r.email_verified(v))
+            .set_opt(self.attribute_query(), |r, v| r.q(v))
+    }
+}
+
+/// a role granted to a user, see [`user_effective_roles`](KeycloakUserExt::user_effective_roles)
+#[derive(Debug, Clone)]
+pub struct EffectiveRole {
+    pub role: RoleRepresentation,
+    /// every way the user is granted the role
+    pub sources: Vec<RoleSource>,
+}
+
+/// how a user is granted a role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoleSource {
+    /// the role is assigned to the user directly
+    Direct,
+    /// the role is assigned to a group the user is a member of, or to one of its parent groups
+    Group { group_id: String, path: String },
+    /// the role is contained in the composite role with the given id
+    Composite { role_id: String },
+}
+
+/// effective roles collected so far, deduplicated by role id
+#[derive(Default)]
+struct EffectiveRoles {
+    roles: Vec<EffectiveRole>,
+    by_id: HashMap<String, usize>,
+}
+
+impl EffectiveRoles {
+    /// add a role, returning whether it wasn't granted before
+    fn add(&mut self, role: RoleRepresentation, source: RoleSource) -> bool {
+        if let Some(&index) = role.id.as_ref().and_then(|id| self.by_id.get(id)) {
+            let sources = &mut self.roles[index].sources;
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+            return false;
+        }
+        if let Some(id) = &role.id {
+            self.by_id.insert(id.clone(), self.roles.len());
+        }
+        self.roles.push(EffectiveRole {
+            role,
+            sources: vec![source],
+        });
+        true
+    }
+
+    fn add_mappings(&mut self, mappings: MappingsRepresentation, source: &RoleSource) {
+        let client_roles = mappings
+            .client_mappings
+            .into_values()
+            .flat_map(|client| client.mappings);
+        for role in mappings.realm_mappings.into_iter().chain(client_roles) {
+            self.add(role, source.clone());
+        }
+    }
+}
+
+/// result of operations on many users, e.g.
+/// [`users_add_realm_roles`](KeycloakUserExt::users_add_realm_roles)
+#[derive(Debug, Default)]
+pub struct BulkResult {
+    /// ids of the users the operation succeeded for
+    pub succeeded: Vec<String>,
+    /// ids of the users the operation failed for, with the error
+    pub failed: Vec<(String, Error)>,
+}
+
+/// result of [`import_users`](KeycloakUserExt::import_users)
+#[derive(Debug, Default)]
+pub struct UserImportResult {
+    /// results of the imported users, including the ones created individually
+    pub results: PartialImportResults,
+    /// index (in the imported users) of the users that couldn't be created individually, with the
+    /// error
+    pub failed: Vec<(usize, Error)>,
+}
+
+/// options for [`user_execute_actions_email`](KeycloakUserExt::user_execute_actions_email)
+#[derive(Debug, Clone, Default)]
+pub struct ExecuteActionsOptions {
+    /// client the user is redirected to after completing the actions
+    pub client_id: Option<String>,
+    /// uri the user is redirected to after completing the actions, requires `client_id`
+    pub redirect_uri: Option<String>,
+    /// validity of the link in the email (defaults to the realm's action token lifespan)
+    pub lifespan: Option<Duration>,
+}
+
 /// user-related methods of the keycloak api
 pub trait KeycloakUserExt {
     /// get all users
     fn users(&self) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
 
+    /// get all users, only including the basic user fields (no attributes etc.)
+    fn users_brief(&self) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+
+    /// get all users as a stream
+    ///
+    /// unlike [`users`](KeycloakUserExt::users), this only fetches the next page of users when
+    /// the previous one has been consumed, so large directories don't have to be kept in memory
+    fn users_stream(
+        &self,
+        brief_representation: Option<bool>,
+    ) -> impl Stream<Item = Result<UserRepresentation>> + Send + '_;
+
+    /// get all users matching the given filters
+    fn search_users(
+        &self,
+        query: &UserQuery,
+    ) -> impl Future<Output = Result<Vec<UserRepresentation>>> + Send;
+
+    /// count the users matching the given filters
+    ///
+    /// keycloak doesn't support the `exact`, `idp_alias` and `idp_user_id` filters when counting
+    /// users, so they are ignored
+    fn users_count(&self, query: &UserQuery) -> impl Future<Output = Result<u64>> + Send;
+
     /// get a single user by their username
     fn user_by_name(
         &self,
         username: &str,
     ) -> impl Future<Output = Result<UserRepresentation>> + Send;
 
+    /// get a single user by their email address
+    ///
+    /// the email is matched exactly. fails with [`ErrorKind::NotUnique`] if multiple users share
+    /// the email (only possible if duplicate emails are allowed in the realm)
+    fn user_by_email(&self, email: &str)
+        -> impl Future<Output = Result<UserRepresentation>> + Send;
+
+    /// get a single user given their uuid
+    ///
+    /// fails with [`ErrorKind::NotFound`] if the user doesn't exist
+    fn user_by_id(&self, user_id: &str) -> impl Future<Output = Result<UserRepresentation>> + Send;
+
+    /// get a single user given their uuid, including the user profile metadata
+    ///
+    /// the metadata describes the user's attributes according to the realm's user profile
+    /// configuration (display names, validators, permissions etc.). attributes not managed by the
+    /// user profile are only returned if the realm's unmanaged attribute policy allows admins to
+    /// view them.
+    fn user_with_profile_metadata(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<UserRepresentation>> + Send;
+
+    /// create a new user
+    ///
+    /// returns the uuid of the created user. if a user with the same username or email already
+    /// exists, this fails with [`ErrorKind::Conflict`]
+    fn create_user(&self, user: &UserRepresentation)
+        -> impl Future<Output = Result<String>> + Send;
+
+    /// create a user or update it if it already exists
+    ///
+    /// existing users are looked up by username, or by email if `user` has no username. fields
+    /// set in `user` are merged into the existing user, all other fields keep their current
+    /// values. fails with [`ErrorKind::Conflict`] if no user has the username but the email
+    /// belongs to another user, instead of renaming that user.
+    fn ensure_user(
+        &self,
+        user: &UserRepresentation,
+    ) -> impl Future<Output = Result<Ensured>> + Send;
+
+    /// import many users at once using the realm's partial import
+    ///
+    /// users are imported in chunks. if keycloak rejects a chunk (e.g. because it contains an
+    /// existing user with [`ImportPolicy::Fail`]), the users of that chunk are created one by one
+    /// using [`create_user`](KeycloakUserExt::create_user) instead, so a single bad user doesn't
+    /// stop the import. users failing individually are reported in the result. existing users are
+    /// reported as failed with [`ErrorKind::Conflict`] then, except for [`ImportPolicy::Skip`]
+    /// where they are counted as skipped (they are not overwritten with
+    /// [`ImportPolicy::Overwrite`] either).
+    fn import_users(
+        &self,
+        users: &[UserRepresentation],
+        policy: ImportPolicy,
+    ) -> impl Future<Output = UserImportResult> + Send;
+
+    /// update an existing user
+    ///
+    /// keycloak replaces the whole user with the given representation, so this should usually
+    /// contain all fields of the user. see [`modify_user`](KeycloakUserExt::modify_user) for
+    /// updating single fields.
+    fn update_user(
+        &self,
+        user_id: &str,
+        user: &UserRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// modify an existing user
+    ///
+    /// this fetches the current user, applies `modify` and writes the result back, so fields not
+    /// touched by `modify` keep their current values. returns the updated user.
+    fn modify_user<F>(
+        &self,
+        user_id: &str,
+        modify: F,
+    ) -> impl Future<Output = Result<UserRepresentation>> + Send
+    where
+        F: FnOnce(&mut UserRepresentation) + Send;
+
+    /// enable or disable a user
+    ///
+    /// disabled users can't log in anymore, but their sessions aren't logged out
+    fn set_user_enabled(
+        &self,
+        user_id: &str,
+        enabled: bool,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// add required actions (e.g. `UPDATE_PASSWORD`) the user has to perform on the next login
+    ///
+    /// actions the user already has to perform are kept
+    fn user_add_required_actions(
+        &self,
+        user_id: &str,
+        actions: &[&str],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// delete a user
+    ///
+    /// fails with [`ErrorKind::NotFound`] if the user doesn't exist (anymore)
+    fn delete_user(&self, user_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// set a new password for a user
+    ///
+    /// if `temporary` is `true`, the user has to change the password on the next login. passwords
+    /// violating the realm's password policy fail with [`ErrorKind::InvalidPassword`]
+    fn user_reset_password(
+        &self,
+        user_id: &str,
+        new_password: &str,
+        temporary: bool,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// send an email asking the user to perform the given required actions
+    ///
+    /// actions are the aliases of required actions, e.g. `UPDATE_PASSWORD`, `CONFIGURE_TOTP` or
+    /// `VERIFY_EMAIL`
+    fn user_execute_actions_email(
+        &self,
+        user_id: &str,
+        actions: &[&str],
+        options: &ExecuteActionsOptions,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// send an email asking the user to verify their email address
+    ///
+    /// if a `client_id` is given, the user is redirected to that client (or `redirect_uri`) after
+    /// the verification
+    fn user_send_verify_email(
+        &self,
+        user_id: &str,
+        client_id: Option<&str>,
+        redirect_uri: Option<&str>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the active sessions of a user
+    fn user_sessions(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<UserSessionRepresentation>>> + Send;
+
+    /// log out a single session (see [`user_sessions`](KeycloakUserExt::user_sessions))
+    fn delete_session(&self, session_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the offline sessions a user holds for the client with the given uuid
+    fn user_offline_sessions(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<UserSessionRepresentation>>> + Send;
+
+    /// log out all sessions of a user
+    ///
+    /// if `include_offline` is `true`, the offline sessions of the user are removed as well. they
+    /// can only be queried per client, so this queries the sessions of every client the user has
+    /// offline tokens for (according to the user's consents).
+    fn user_logout(
+        &self,
+        user_id: &str,
+        include_offline: bool,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the brute force detection status of a user
+    fn user_brute_force_status(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<BruteForceStatus>> + Send;
+
+    /// clear the login failures of a user, unlocking a temporarily locked user
+    fn user_clear_login_failures(&self, user_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the identity provider links of a user
+    fn user_federated_identities(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<FederatedIdentityRepresentation>>> + Send;
+
+    /// link a user to an account at an identity provider
+    ///
+    /// `identity.identity_provider` is ignored, the link is always created for `provider` (the
+    /// alias of the identity provider). fails with [`ErrorKind::Conflict`] if the user is already
+    /// linked to the provider.
+    fn user_add_federated_identity(
+        &self,
+        user_id: &str,
+        provider: &str,
+        identity: &FederatedIdentityRepresentation,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove the link of a user to an identity provider
+    fn user_remove_federated_identity(
+        &self,
+        user_id: &str,
+        provider: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the stored credentials (passwords, otp devices etc.) of a user
+    ///
+    /// the credentials are ordered by priority
+    fn user_credentials(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<CredentialRepresentation>>> + Send;
+
+    /// delete a stored credential of a user
+    fn user_delete_credential(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// move a credential of a user directly after another one in the priority order
+    fn user_move_credential_after(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+        previous_credential_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// move a credential of a user to the first position in the priority order
+    fn user_move_credential_to_first(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// set the label shown for a credential of a user
+    fn user_set_credential_label(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+        label: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get the declarative user profile configuration of the realm
+    fn user_profile_config(&self) -> impl Future<Output = Result<UpConfig>> + Send;
+
+    /// get the user profile metadata of the realm, describing the attributes of new users
+    fn user_profile_metadata(&self) -> impl Future<Output = Result<UserProfileMetadata>> + Send;
+
+    /// replace the declarative user profile configuration of the realm
+    ///
+    /// returns the configuration as stored by keycloak
+    fn update_user_profile_config(
+        &self,
+        config: &UpConfig,
+    ) -> impl Future<Output = Result<UpConfig>> + Send;
+
     /// get a user's realm roles given their uuid
     fn user_realm_roles(
         &self,
@@ -28,42 +542,153 @@ pub trait KeycloakUserExt {
     fn user_add_realm_roles(
         &self,
         user_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
     ) -> impl Future<Output = Result<()>> + Send;
 
     /// remove realm roles from a user
     fn user_remove_realm_roles(
         &self,
         user_id: &str,
-        roles: &Vec<RoleRepresentation>,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// add realm roles to many users
+    ///
+    /// the users are processed in chunks with several requests running concurrently. failing
+    /// users don't stop the operation, they are reported in the result instead.
+    fn users_add_realm_roles(
+        &self,
+        user_ids: &[&str],
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<BulkResult>> + Send;
+
+    /// add roles of the client with the given uuid to many users
+    ///
+    /// see [`users_add_realm_roles`](KeycloakUserExt::users_add_realm_roles)
+    fn users_add_client_roles(
+        &self,
+        user_ids: &[&str],
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<BulkResult>> + Send;
+
+    /// get a user's roles of the client with the given uuid
+    fn user_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// add roles of the client with the given uuid to a user
+    fn user_add_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// remove roles of the client with the given uuid from a user
+    fn user_remove_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
     ) -> impl Future<Output = Result<()>> + Send;
+
+    /// get all roles granted to a user and how they are granted
+    ///
+    /// this includes roles assigned directly, roles of the user's groups and their parent groups
+    /// and the roles contained in any of these roles if they are composite roles (recursively).
+    /// every role is returned once, with all of its sources.
+    fn user_effective_roles(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<EffectiveRole>>> + Send;
+
+    /// get the realm roles that can still be assigned to a user
+    fn user_available_realm_roles(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// get the realm roles of a user including those granted through composite roles
+    fn user_composite_realm_roles(
+        &self,
+        user_id: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// get the roles of the client with the given uuid that can still be assigned to a user
+    fn user_available_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
+
+    /// get the roles of the client with the given uuid of a user including those granted through
+    /// composite roles
+    fn user_composite_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> impl Future<Output = Result<Vec<RoleRepresentation>>> + Send;
 }
 
 impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakUserExt for crate::Keycloak<A> {
     #[tracing::instrument(skip(self))]
     async fn users(&self) -> Result<Vec<UserRepresentation>> {
+        all_users(self, false).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn users_brief(&self) -> Result<Vec<UserRepresentation>> {
+        all_users(self, true).await
+    }
+
+    fn users_stream(
+        &self,
+        brief_representation: Option<bool>,
+    ) -> impl Stream<Item = Result<UserRepresentation>> + Send + '_ {
+        const PAGE_MAX: i32 = 100;
+
+        let pages = stream::try_unfold(Some(0), move |page_offset| async move {
+            let Some(first) = page_offset else {
+                return Ok(None);
+            };
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!(first, "querying page of users");
+            let page = api_client
+                .get_realm_users()
+                .realm(&self.config.realm)
+                .set_opt(brief_representation, |r, v| r.brief_representation(v))
+                .first(first)
+                .max(PAGE_MAX)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner();
+            // next page
+            let page_offset = (page.len() == PAGE_MAX as usize).then_some(first + PAGE_MAX);
+            Ok(Some((page, page_offset)))
+        });
+        pages
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn search_users(&self, query: &UserQuery) -> Result<Vec<UserRepresentation>> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
+        tracing::debug!("searching users");
         let response = paginate_api!(|first, max| {
-            api_client
-                .get_realm_users(
-                    &self.config.realm,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    Some(first),
-                    None,
-                    None,
-                    None,
-                    None,
-                    Some(max),
-                    None,
-                    None,
-                    None,
-                )
+            query
+                .apply(api_client.get_realm_users().realm(&self.config.realm))
+                .first(first)
+                .max(max)
+                .send()
                 .await
                 .map_err(crate::error::progenitor)?
                 .into_inner()
@@ -72,6 +697,21 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakUserExt for crate::
         Ok(response)
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn users_count(&self, query: &UserQuery) -> Result<u64> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("counting users");
+        let count = query
+            .apply_count(api_client.get_realm_users_count().realm(&self.config.realm))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        super::count_from(count)
+    }
+
     #[tracing::instrument(skip(self))]
     async fn user_by_name(&self, username: &str) -> Result<UserRepresentation> {
         self.refresh_if_necessary().await?;
@@ -79,23 +719,11 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakUserExt for crate::
 
         tracing::debug!("querying user by name");
         let mut response = api_client
-            .get_realm_users(
-                &self.config.realm,
-                None,
-                None,
-                None,
-                None,
-                Some(true),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some(username),
-            )
+            .get_realm_users()
+            .realm(&self.config.realm)
+            .exact(true)
+            .username(username)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
@@ -114,52 +742,1203 @@ impl<A: crate::AuthenticationProvider + Send + Sync> KeycloakUserExt for crate::
     }
 
     #[tracing::instrument(skip(self))]
-    async fn user_realm_roles(&self, user_id: &str) -> Result<Vec<RoleRepresentation>> {
+    async fn user_by_email(&self, email: &str) -> Result<UserRepresentation> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("querying user realm roles");
-        let response = api_client
-            .get_realm_user_role_mappings_realm(&self.config.realm, user_id)
+        tracing::debug!("querying user by email");
+        let mut response = api_client
+            .get_realm_users()
+            .realm(&self.config.realm)
+            .exact(true)
+            .email(email)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
-        Ok(response)
+        if response.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotFound(ResourceType::User)));
+        }
+        let user = response.pop().unwrap();
+        if !response.is_empty() {
+            return Err(Error::new_kind(ErrorKind::NotUnique(ResourceType::User)));
+        }
+        Ok(user)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn user_add_realm_roles(
-        &self,
-        user_id: &str,
-        roles: &Vec<RoleRepresentation>,
-    ) -> Result<()> {
+    async fn user_by_id(&self, user_id: &str) -> Result<UserRepresentation> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("adding roles to user");
+        tracing::debug!("querying user by id");
         let response = api_client
-            .post_realm_user_role_mappings_realm(&self.config.realm, user_id, roles)
+            .get_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
             .await
-            .map_err(crate::error::progenitor)?
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::User),
+                )
+            })?
             .into_inner();
         Ok(response)
     }
 
     #[tracing::instrument(skip(self))]
-    async fn user_remove_realm_roles(
-        &self,
-        user_id: &str,
-        roles: &Vec<RoleRepresentation>,
-    ) -> Result<()> {
+    async fn user_with_profile_metadata(&self, user_id: &str) -> Result<UserRepresentation> {
         self.refresh_if_necessary().await?;
         let api_client = self.api_client.read().await;
 
-        tracing::debug!("removing roles from user");
+        tracing::debug!("querying user by id with profile metadata");
         let response = api_client
-            .delete_realm_user_role_mappings_realm(&self.config.realm, user_id, roles)
+            .get_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .user_profile_metadata(true)
+            .send()
             .await
             .map_err(crate::error::progenitor)?
             .into_inner();
         Ok(response)
     }
+
+    #[tracing::instrument(skip(self, user), fields(username = user.username))]
+    async fn create_user(&self, user: &UserRepresentation) -> Result<String> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("creating user");
+        let response = api_client
+            .post_realm_users()
+            .realm(&self.config.realm)
+            .body(user.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::User),
+                )
+            })?;
+        super::id_from_location(&response)
+    }
+
+    #[tracing::instrument(skip(self, user), fields(username = user.username))]
+    async fn ensure_user(&self, user: &UserRepresentation) -> Result<Ensured> {
+        let existing = match find_existing_user(self, user).await? {
+            Some(existing) => existing,
+            None => match self.create_user(user).await {
+                Ok(id) => return Ok(Ensured::Created(id)),
+                // created concurrently, update that user instead
+                Err(e) if matches!(e.kind(), ErrorKind::Conflict(_)) => {
+                    tracing::debug!("user was created concurrently");
+                    find_existing_user(self, user).await?.ok_or(e)?
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        let Some(user_id) = existing.id.clone() else {
+            return Err(Error::new_kind(ErrorKind::MissingId));
+        };
+        let mut merged = merge_representation(&existing, user)?;
+        merged.id = Some(user_id.clone());
+        self.update_user(&user_id, &merged).await?;
+        Ok(Ensured::Updated(user_id))
+    }
+
+    #[tracing::instrument(skip(self, users), fields(users = users.len()))]
+    async fn import_users(
+        &self,
+        users: &[UserRepresentation],
+        policy: ImportPolicy,
+    ) -> UserImportResult {
+        const CHUNK_SIZE: usize = 500;
+
+        let mut result = UserImportResult::default();
+        for (i, chunk) in users.chunks(CHUNK_SIZE).enumerate() {
+            tracing::debug!(chunk = chunk.len(), "importing users");
+            let mut import = PartialImportRepresentation::new(policy);
+            import.users = chunk.to_vec();
+            match self.realm_partial_import(&import).await {
+                Ok(chunk_results) => {
+                    result.results.extend(chunk_results);
+                    continue;
+                }
+                // the partial import is transactional, so none of the chunk's users were imported
+                Err(e) => tracing::debug!(
+                    "failed to import chunk of users, creating them one by one: {e}"
+                ),
+            }
+            for (j, user) in chunk.iter().enumerate() {
+                match self.create_user(user).await {
+                    Ok(id) => {
+                        result.results.added += 1;
+                        result.results.results.push(PartialImportResult {
+                            action: ImportAction::Added,
+                            resource_type: "USER".into(),
+                            resource_name: user.username.clone(),
+                            id: Some(id),
+                        });
+                    }
+                    Err(e)
+                        if policy == ImportPolicy::Skip
+                            && matches!(e.kind(), ErrorKind::Conflict(_)) =>
+                    {
+                        result.results.skipped += 1;
+                        result.results.results.push(PartialImportResult {
+                            action: ImportAction::Skipped,
+                            resource_type: "USER".into(),
+                            resource_name: user.username.clone(),
+                            id: None,
+                        });
+                    }
+                    Err(e) => {
+                        tracing::debug!(username = user.username, "failed to create user: {e}");
+                        result.failed.push((i * CHUNK_SIZE + j, e));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    #[tracing::instrument(skip(self, user))]
+    async fn update_user(&self, user_id: &str, user: &UserRepresentation) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating user");
+        api_client
+            .put_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .body(user.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, modify))]
+    async fn modify_user<F>(&self, user_id: &str, modify: F) -> Result<UserRepresentation>
+    where
+        F: FnOnce(&mut UserRepresentation) + Send,
+    {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user to modify");
+        let mut user = api_client
+            .get_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        modify(&mut user);
+
+        tracing::debug!("updating user");
+        api_client
+            .put_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .body(user.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(user)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn set_user_enabled(&self, user_id: &str, enabled: bool) -> Result<()> {
+        self.modify_user(user_id, |user| user.enabled = Some(enabled))
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_add_required_actions(&self, user_id: &str, actions: &[&str]) -> Result<()> {
+        self.modify_user(user_id, |user| {
+            for &action in actions {
+                if !user.required_actions.iter().any(|a| a == action) {
+                    user.required_actions.push(action.into());
+                }
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_user(&self, user_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting user");
+        api_client
+            .delete_realm_user()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)
+            .map_err(|e| {
+                e.map_status(
+                    StatusCode::NOT_FOUND,
+                    ErrorKind::NotFound(ResourceType::User),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, new_password))]
+    async fn user_reset_password(
+        &self,
+        user_id: &str,
+        new_password: &str,
+        temporary: bool,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("resetting user password");
+        let result = api_client
+            .put_realm_user_reset_password()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .body_map(|credential| {
+                credential
+                    .type_("password".to_string())
+                    .value(new_password.to_string())
+                    .temporary(temporary)
+            })
+            .send()
+            .await;
+        if let Err(e) = result {
+            let e = crate::error::progenitor_response(e).await;
+            // password policy violations are reported as bad request
+            if e.status() == Some(StatusCode::BAD_REQUEST) {
+                if let Some(body) = e.keycloak_error() {
+                    let message = body.error_description.as_ref().unwrap_or(&body.error);
+                    let kind = ErrorKind::InvalidPassword(message.clone());
+                    return Err(Error::new(kind, Some(e)));
+                }
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_execute_actions_email(
+        &self,
+        user_id: &str,
+        actions: &[&str],
+        options: &ExecuteActionsOptions,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        let lifespan = options
+            .lifespan
+            .map(|l| l.as_secs().try_into().unwrap_or(i32::MAX));
+        tracing::debug!("sending required actions email");
+        api_client
+            .put_realm_user_execute_actions_email()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .set_opt(options.client_id.as_deref(), |r, v| r.client_id(v))
+            .set_opt(options.redirect_uri.as_deref(), |r, v| r.redirect_uri(v))
+            .set_opt(lifespan, |r, v| r.lifespan(v))
+            .body(actions.iter().map(|&a| a.into()).collect::<Vec<String>>())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_send_verify_email(
+        &self,
+        user_id: &str,
+        client_id: Option<&str>,
+        redirect_uri: Option<&str>,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("sending verification email");
+        api_client
+            .put_realm_user_send_verify_email()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .set_opt(client_id, |r, v| r.client_id(v))
+            .set_opt(redirect_uri, |r, v| r.redirect_uri(v))
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_sessions(&self, user_id: &str) -> Result<Vec<UserSessionRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user sessions");
+        let response = api_client
+            .get_realm_user_sessions()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting session");
+        api_client
+            .delete_realm_session()
+            .realm(&self.config.realm)
+            .session(session_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_offline_sessions(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> Result<Vec<UserSessionRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying offline sessions of user");
+        let response = api_client
+            .get_realm_user_offline_sessions_client_uuid()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_uuid(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_logout(&self, user_id: &str, include_offline: bool) -> Result<()> {
+        {
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("logging out user");
+            api_client
+                .post_realm_user_logout()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?;
+        }
+        if !include_offline {
+            return Ok(());
+        }
+
+        let consents = {
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("querying consents of user");
+            api_client
+                .get_realm_user_consents()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner()
+        };
+        // offline sessions can only be queried per client
+        for client_id in offline_token_clients(&consents) {
+            let Some(client_uuid) = self.client_by_id(client_id).await?.id else {
+                continue;
+            };
+            let sessions = self.user_offline_sessions(user_id, &client_uuid).await?;
+
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+            for session_id in sessions.into_iter().filter_map(|s| s.id) {
+                tracing::debug!(session_id, client_uuid, "removing offline session");
+                api_client
+                    .delete_realm_session()
+                    .realm(&self.config.realm)
+                    .session(session_id)
+                    .is_offline(true)
+                    .send()
+                    .await
+                    .map_err(crate::error::progenitor)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_brute_force_status(&self, user_id: &str) -> Result<BruteForceStatus> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying brute force status of user");
+        let response = api_client
+            .get_realm_attack_detection_brute_force_users_user_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        serde_json::from_value(response.into()).map_err(crate::error::deserialize)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_clear_login_failures(&self, user_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("clearing login failures of user");
+        api_client
+            .delete_realm_attack_detection_brute_force_users_user_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_federated_identities(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<FederatedIdentityRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying federated identities of user");
+        let response = api_client
+            .get_realm_user_federated_identity()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, identity))]
+    async fn user_add_federated_identity(
+        &self,
+        user_id: &str,
+        provider: &str,
+        identity: &FederatedIdentityRepresentation,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("linking user to identity provider");
+        api_client
+            .post_realm_user_federated_identity_provider()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .provider(provider)
+            .body(identity.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                crate::error::progenitor(e).map_status(
+                    StatusCode::CONFLICT,
+                    ErrorKind::Conflict(ResourceType::User),
+                )
+            })?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_remove_federated_identity(&self, user_id: &str, provider: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("unlinking user from identity provider");
+        api_client
+            .delete_realm_user_federated_identity_provider()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .provider(provider)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_credentials(&self, user_id: &str) -> Result<Vec<CredentialRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user credentials");
+        let response = api_client
+            .get_realm_user_credentials()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_delete_credential(&self, user_id: &str, credential_id: &str) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("deleting user credential");
+        api_client
+            .delete_realm_user_credentials_credential_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .credential_id(credential_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_move_credential_after(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+        previous_credential_id: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("moving user credential");
+        api_client
+            .post_realm_user_credentials_credential_id_move_after_new_previous_credential_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .credential_id(credential_id)
+            .new_previous_credential_id(previous_credential_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_move_credential_to_first(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("moving user credential to first position");
+        api_client
+            .post_realm_user_credentials_credential_id_move_to_first()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .credential_id(credential_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_set_credential_label(
+        &self,
+        user_id: &str,
+        credential_id: &str,
+        label: &str,
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("setting user credential label");
+        api_client
+            .put_realm_user_credentials_credential_id_user_label()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .credential_id(credential_id)
+            .body(label)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_profile_config(&self) -> Result<UpConfig> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user profile config");
+        let response = api_client
+            .get_realm_users_profile()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_profile_metadata(&self) -> Result<UserProfileMetadata> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user profile metadata");
+        let response = api_client
+            .get_realm_users_profile_metadata()
+            .realm(&self.config.realm)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self, config))]
+    async fn update_user_profile_config(&self, config: &UpConfig) -> Result<UpConfig> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("updating user profile config");
+        let response = api_client
+            .put_realm_users_profile()
+            .realm(&self.config.realm)
+            .body(config.clone())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_realm_roles(&self, user_id: &str) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user realm roles");
+        let response = api_client
+            .get_realm_user_role_mappings_realm()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_add_realm_roles(
+        &self,
+        user_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding roles to user");
+        api_client
+            .post_realm_user_role_mappings_realm()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_remove_realm_roles(
+        &self,
+        user_id: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing roles from user");
+        api_client
+            .delete_realm_user_role_mappings_realm()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, user_ids), fields(users = user_ids.len()))]
+    async fn users_add_realm_roles(
+        &self,
+        user_ids: &[&str],
+        roles: &[RoleRepresentation],
+    ) -> Result<BulkResult> {
+        bulk_add_roles(self, user_ids, None, roles).await
+    }
+
+    #[tracing::instrument(skip(self, user_ids), fields(users = user_ids.len()))]
+    async fn users_add_client_roles(
+        &self,
+        user_ids: &[&str],
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<BulkResult> {
+        bulk_add_roles(self, user_ids, Some(client_uuid), roles).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying user client roles");
+        let response = api_client
+            .get_realm_user_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_id(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_add_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("adding client roles to user");
+        api_client
+            .post_realm_user_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_id(client_uuid)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_remove_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+        roles: &[RoleRepresentation],
+    ) -> Result<()> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("removing client roles from user");
+        api_client
+            .delete_realm_user_role_mappings_clients_client_id()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_id(client_uuid)
+            .body(roles.to_vec())
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_effective_roles(&self, user_id: &str) -> Result<Vec<EffectiveRole>> {
+        let (mappings, groups) = {
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!("querying user role mappings");
+            let mappings = api_client
+                .get_realm_user_role_mappings()
+                .realm(&self.config.realm)
+                .user_id(user_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner();
+            tracing::debug!("querying user groups");
+            let groups = paginate_api!(|first, max| {
+                api_client
+                    .get_realm_user_groups()
+                    .realm(&self.config.realm)
+                    .user_id(user_id)
+                    .brief_representation(true)
+                    .first(first)
+                    .max(max)
+                    .send()
+                    .await
+                    .map_err(crate::error::progenitor)?
+                    .into_inner()
+            });
+            (mappings, groups)
+        };
+        let mut roles = EffectiveRoles::default();
+        roles.add_mappings(mappings, &RoleSource::Direct);
+
+        // members of a group inherit the roles of its parent groups
+        let mut group_ids: HashMap<String, String> = groups
+            .into_iter()
+            .filter_map(|group| Some((group.path?, group.id?)))
+            .collect();
+        let mut paths: Vec<String> = Vec::new();
+        for path in group_ids.keys() {
+            let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+            for i in 1..=segments.len() {
+                let prefix = format!("/{}", segments[..i].join("/"));
+                if !paths.contains(&prefix) {
+                    paths.push(prefix);
+                }
+            }
+        }
+        for path in paths {
+            let group_id = match group_ids.remove(&path) {
+                Some(id) => id,
+                None => self
+                    .group_by_path(&path)
+                    .await?
+                    .id
+                    .ok_or_else(|| Error::new_kind(ErrorKind::MissingId))?,
+            };
+            self.refresh_if_necessary().await?;
+            let api_client = self.api_client.read().await;
+
+            tracing::debug!(path, "querying group role mappings");
+            let mappings = api_client
+                .get_realm_group_role_mappings()
+                .realm(&self.config.realm)
+                .group_id(&group_id)
+                .send()
+                .await
+                .map_err(crate::error::progenitor)?
+                .into_inner();
+            roles.add_mappings(mappings, &RoleSource::Group { group_id, path });
+        }
+
+        // every composite role is only expanded once, so cycles between composites are fine
+        let mut pending: Vec<String> = roles
+            .roles
+            .iter()
+            .filter(|effective| effective.role.composite == Some(true))
+            .filter_map(|effective| effective.role.id.clone())
+            .collect();
+        while let Some(role_id) = pending.pop() {
+            for role in self.role_composites_by_id(&role_id).await? {
+                let child = (role.composite == Some(true))
+                    .then(|| role.id.clone())
+                    .flatten();
+                let source = RoleSource::Composite {
+                    role_id: role_id.clone(),
+                };
+                if roles.add(role, source) {
+                    pending.extend(child);
+                }
+            }
+        }
+
+        Ok(roles.roles)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_available_realm_roles(&self, user_id: &str) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying available realm roles of user");
+        let response = api_client
+            .get_realm_user_role_mappings_realm_available()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_composite_realm_roles(&self, user_id: &str) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying composite realm roles of user");
+        let response = api_client
+            .get_realm_user_role_mappings_realm_composite()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_available_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying available client roles of user");
+        let response = api_client
+            .get_realm_user_role_mappings_clients_client_id_available()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_id(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn user_composite_client_roles(
+        &self,
+        user_id: &str,
+        client_uuid: &str,
+    ) -> Result<Vec<RoleRepresentation>> {
+        self.refresh_if_necessary().await?;
+        let api_client = self.api_client.read().await;
+
+        tracing::debug!("querying composite client roles of user");
+        let response = api_client
+            .get_realm_user_role_mappings_clients_client_id_composite()
+            .realm(&self.config.realm)
+            .user_id(user_id)
+            .client_id(client_uuid)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner();
+        Ok(response)
+    }
+}
+
+/// add realm roles (or client roles if `client_uuid` is given) to many users
+async fn bulk_add_roles<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    user_ids: &[&str],
+    client_uuid: Option<&str>,
+    roles: &[RoleRepresentation],
+) -> Result<BulkResult> {
+    // the token is only refreshed between chunks, so they shouldn't take too long
+    const CHUNK_SIZE: usize = 100;
+    const CONCURRENCY: usize = 8;
+
+    let mut result = BulkResult::default();
+    for chunk in user_ids.chunks(CHUNK_SIZE) {
+        keycloak.refresh_if_necessary().await?;
+        let api_client = keycloak.api_client.read().await;
+
+        tracing::debug!(users = chunk.len(), "adding roles to chunk of users");
+        let api_client = &*api_client;
+        // collected first, the lazy iterator trips up the `Send` check of the returned future
+        let requests: Vec<_> = chunk
+            .iter()
+            .map(|&user_id| async move {
+                let response = match client_uuid {
+                    Some(client_uuid) => {
+                        api_client
+                            .post_realm_user_role_mappings_clients_client_id()
+                            .realm(&keycloak.config.realm)
+                            .user_id(user_id)
+                            .client_id(client_uuid)
+                            .body(roles.to_vec())
+                            .send()
+                            .await
+                    }
+                    None => {
+                        api_client
+                            .post_realm_user_role_mappings_realm()
+                            .realm(&keycloak.config.realm)
+                            .user_id(user_id)
+                            .body(roles.to_vec())
+                            .send()
+                            .await
+                    }
+                };
+                (user_id, response.map_err(crate::error::progenitor))
+            })
+            .collect();
+        let results: Vec<_> = stream::iter(requests)
+            .buffer_unordered(CONCURRENCY)
+            .collect()
+            .await;
+        for (user_id, response) in results {
+            match response {
+                Ok(_) => result.succeeded.push(user_id.into()),
+                Err(e) => {
+                    tracing::debug!(user_id, "failed to add roles to user: {e}");
+                    result.failed.push((user_id.into(), e));
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// find the existing user for [`ensure_user`](KeycloakUserExt::ensure_user)
+async fn find_existing_user<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    user: &UserRepresentation,
+) -> Result<Option<UserRepresentation>> {
+    let not_found = |e: &Error| matches!(e.kind(), ErrorKind::NotFound(_));
+    if let Some(username) = &user.username {
+        match keycloak.user_by_name(username).await {
+            Ok(existing) => return Ok(Some(existing)),
+            Err(e) if not_found(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let Some(email) = &user.email else {
+        return Ok(None);
+    };
+    match keycloak.user_by_email(email).await {
+        // the email belongs to a user with a different username
+        Ok(_) if user.username.is_some() => {
+            Err(Error::new_kind(ErrorKind::Conflict(ResourceType::User)))
+        }
+        Ok(existing) => Ok(Some(existing)),
+        Err(e) if not_found(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// client ids of the clients with offline tokens in a user's consents
+///
+/// keycloak lists these clients with an `Offline Token` grant, even if they don't require consent
+fn offline_token_clients(consents: &[serde_json::Map<String, serde_json::Value>]) -> Vec<&str> {
+    consents
+        .iter()
+        .filter(|consent| {
+            consent
+                .get("additionalGrants")
+                .and_then(|grants| grants.as_array())
+                .is_some_and(|grants| {
+                    grants
+                        .iter()
+                        .any(|grant| grant["key"].as_str() == Some("Offline Token"))
+                })
+        })
+        .filter_map(|consent| consent.get("clientId")?.as_str())
+        .collect()
+}
+
+async fn all_users<A: crate::AuthenticationProvider + Send + Sync>(
+    keycloak: &crate::Keycloak<A>,
+    brief_representation: bool,
+) -> Result<Vec<UserRepresentation>> {
+    keycloak.refresh_if_necessary().await?;
+    let api_client = keycloak.api_client.read().await;
+
+    let response = paginate_api!(|first, max| {
+        api_client
+            .get_realm_users()
+            .realm(&keycloak.config.realm)
+            .brief_representation(brief_representation)
+            .first(first)
+            .max(max)
+            .send()
+            .await
+            .map_err(crate::error::progenitor)?
+            .into_inner()
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{offline_token_clients, EffectiveRoles, RoleSource};
+
+    #[test]
+    fn test_offline_token_clients() {
+        let consents: Vec<serde_json::Map<String, serde_json::Value>> =
+            serde_json::from_value(serde_json::json!([
+                { "clientId": "consent-only", "grantedClientScopes": ["profile"] },
+                {
+                    "clientId": "offline",
+                    "additionalGrants": [{ "client": "Offline Token", "key": "Offline Token" }],
+                },
+            ]))
+            .unwrap();
+        assert_eq!(offline_token_clients(&consents), ["offline"]);
+    }
+
+    #[test]
+    fn test_effective_roles() {
+        let role = |id: &str| serde_json::from_value(serde_json::json!({ "id": id })).unwrap();
+        let group = RoleSource::Group {
+            group_id: "group".into(),
+            path: "/group".into(),
+        };
+
+        let mut roles = EffectiveRoles::default();
+        assert!(roles.add(role("a"), RoleSource::Direct));
+        assert!(roles.add(role("b"), group.clone()));
+        assert!(!roles.add(role("a"), group.clone()));
+        assert!(!roles.add(role("a"), RoleSource::Direct));
+
+        assert_eq!(roles.roles.len(), 2);
+        assert_eq!(roles.roles[0].sources, [RoleSource::Direct, group]);
+    }
 }
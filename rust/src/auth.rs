@@ -1,9 +1,25 @@
 use std::future::Future;
 use std::time::{Duration, Instant};
 
-use crate::error::ErrorKind;
+use base64::Engine;
+
+use crate::error::{ErrorKind, InnerError};
 use crate::KeycloakConfig;
 
+/// decode the claims of an access token
+///
+/// this does **not** validate the token in any way and must only be used for tokens obtained from
+/// a trusted source
+pub fn decode_token_claims(access_token: &str) -> Result<crate::rest::TokenClaims, crate::Error> {
+    let Some(payload) = access_token.split('.').nth(1) else {
+        return Err(crate::Error::new_kind(ErrorKind::Deserialize));
+    };
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| crate::Error::new(ErrorKind::Deserialize, Some(InnerError::from_any(e))))?;
+    serde_json::from_slice(&payload).map_err(crate::error::deserialize)
+}
+
 /// trait for handling authentication to keycloak
 ///
 /// most users of this crate should probably use the [`DirectGrantAuth`] implementation that logs in using keycloak's
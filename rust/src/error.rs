@@ -14,8 +14,22 @@ pub struct KeycloakError {
 
 #[derive(Debug, Copy, Clone)]
 pub enum ResourceType {
+    AuthenticationExecution,
+    AuthenticationFlow,
+    AuthenticatorConfig,
+    AuthzResource,
+    AuthzScope,
     Client,
+    ClientScope,
+    Component,
     Group,
+    IdentityProvider,
+    Organization,
+    PermissionTicket,
+    Policy,
+    Realm,
+    RequiredAction,
+    Role,
     User,
 }
 
@@ -44,12 +58,22 @@ pub enum ErrorKind {
     NotFound(ResourceType),
     #[error("multiple matching {0} resources returned")]
     NotUnique(ResourceType),
+    #[error("conflicting {0} resource already exists")]
+    Conflict(ResourceType),
+    #[error("{0} resource still has members")]
+    NotEmpty(ResourceType),
     #[error("missing id")]
     MissingId,
     #[error("missing field in data: {0}")]
     MissingField(String),
     #[error("wrong type (expected {0}, got {1})")]
     WrongType(String, String),
+    #[error("password rejected: {0}")]
+    InvalidPassword(String),
+    #[error("login flow check failed: {0}")]
+    FlowCheck(String),
+    #[error("destructive operation not confirmed")]
+    NotConfirmed,
     #[error("unspecified error")]
     Other,
 }
@@ -80,6 +104,26 @@ impl KeycloakError {
         &self.kind
     }
 
+    /// wrap this error in a more specific error kind if it was caused by a http response with the given status code
+    pub(crate) fn map_status(self, status: StatusCode, kind: ErrorKind) -> Self {
+        if self.status() == Some(status) {
+            Self::new(kind, Some(self))
+        } else {
+            self
+        }
+    }
+
+    /// get the error body returned by keycloak (if any)
+    pub fn keycloak_error(&self) -> Option<&KeycloakErrorBody> {
+        if let ErrorKind::KeycloakError(body) = self.kind() {
+            return Some(body);
+        }
+        match self.source.as_ref()? {
+            InnerError::Keycloak(e) => e.keycloak_error(),
+            _ => None,
+        }
+    }
+
     /// get the http response status code associated with this error (if any)
     pub fn status(&self) -> Option<StatusCode> {
         if let ErrorKind::ResponseError { status, .. } = self.kind() {
@@ -157,6 +201,19 @@ pub fn progenitor(err: progenitor_client::Error) -> KeycloakError {
     KeycloakError::new(ErrorKind::ApiError, Some(inner))
 }
 
+/// like [`progenitor`], but also reads the body of responses not listed in the api spec
+///
+/// keycloak returns error details for many status codes the spec doesn't mention, which are lost
+/// otherwise
+pub async fn progenitor_response(err: progenitor_client::Error) -> KeycloakError {
+    match err {
+        progenitor_client::Error::UnexpectedResponse(response) => {
+            KeycloakError::new(ErrorKind::ApiError, Some(error_response(response).await))
+        }
+        err => progenitor(err),
+    }
+}
+
 impl Display for KeycloakErrorBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}", self.error))?;
@@ -170,8 +227,22 @@ impl Display for KeycloakErrorBody {
 impl Display for ResourceType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::AuthenticationExecution => write!(f, "authentication execution"),
+            Self::AuthenticationFlow => write!(f, "authentication flow"),
+            Self::AuthenticatorConfig => write!(f, "authenticator config"),
+            Self::AuthzResource => write!(f, "authorization resource"),
+            Self::AuthzScope => write!(f, "authorization scope"),
             Self::Client => write!(f, "client"),
+            Self::ClientScope => write!(f, "client scope"),
+            Self::Component => write!(f, "component"),
             Self::Group => write!(f, "group"),
+            Self::IdentityProvider => write!(f, "identity provider"),
+            Self::Organization => write!(f, "organization"),
+            Self::PermissionTicket => write!(f, "permission ticket"),
+            Self::Policy => write!(f, "policy"),
+            Self::Realm => write!(f, "realm"),
+            Self::RequiredAction => write!(f, "required action"),
+            Self::Role => write!(f, "role"),
             Self::User => write!(f, "user"),
         }
     }
@@ -184,7 +255,9 @@ pub enum InnerError {
     // boxed here instead of in `KeycloakError` to prevent a double box for `Other`
     Keycloak(Box<KeycloakError>),
     Serde(serde_json::Error),
-    Progenitor(progenitor_client::Error<()>),
+    // boxed as it is much larger than the other variants, which would make every `Result` of this
+    // crate large
+    Progenitor(Box<progenitor_client::Error<()>>),
     Reqwest(reqwest::Error),
     Other(Box<dyn std::error::Error + Send + Sync>),
 }
@@ -206,7 +279,7 @@ impl std::ops::Deref for InnerError {
         match self {
             Self::Keycloak(e) => e,
             Self::Serde(e) => e,
-            Self::Progenitor(e) => e,
+            Self::Progenitor(e) => &**e,
             Self::Reqwest(e) => e,
             Self::Other(e) => e.deref(),
         }
@@ -227,7 +300,7 @@ impl From<serde_json::Error> for InnerError {
 
 impl From<progenitor_client::Error<()>> for InnerError {
     fn from(value: progenitor_client::Error<()>) -> Self {
-        Self::Progenitor(value)
+        Self::Progenitor(Box::new(value))
     }
 }
 
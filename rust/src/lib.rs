@@ -3,7 +3,12 @@ mod macros;
 pub mod api;
 pub mod auth;
 pub mod error;
+#[cfg(feature = "tower")]
+pub mod middleware;
+pub mod registration;
 pub mod rest;
+pub mod smoke;
+pub mod uma;
 pub mod util;
 
 use self::auth::AuthenticationProvider;
@@ -16,8 +21,11 @@ use self::util::WithClientAsyncFn;
 
 pub mod prelude {
     pub use crate::api::{
-        KeycloakClientExt, KeycloakGroupExt, KeycloakRealmExt, KeycloakRoleExt, KeycloakUserExt,
+        KeycloakAnalysisExt, KeycloakAuthenticationExt, KeycloakClientExt, KeycloakComponentExt,
+        KeycloakEventExt, KeycloakGroupExt, KeycloakIdentityProviderExt, KeycloakOrganizationExt,
+        KeycloakRealmExt, KeycloakRoleExt, KeycloakUserExt,
     };
+    pub use crate::rest::types::HasAttributes;
     pub use crate::Keycloak;
 }
 
@@ -27,6 +35,37 @@ pub struct KeycloakConfig {
     pub realm: String,
 }
 
+impl KeycloakConfig {
+    /// url of a path below the base url
+    ///
+    /// every segment is percent-encoded on its own, so segments may contain e.g. `/`
+    pub(crate) fn url<'a>(
+        &self,
+        segments: impl IntoIterator<Item = &'a str>,
+    ) -> Result<reqwest::Url, crate::Error> {
+        let mut url = reqwest::Url::parse(&self.base_url).map_err(|e| {
+            Error::new(ErrorKind::Other, Some(self::error::InnerError::from_any(e)))
+        })?;
+        url.path_segments_mut()
+            .map_err(|()| Error::new_kind(ErrorKind::Other))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// url of an endpoint of the realm's admin api, see [`url`](Self::url)
+    pub(crate) fn admin_url<'a>(
+        &'a self,
+        segments: &[&'a str],
+    ) -> Result<reqwest::Url, crate::Error> {
+        self.url(
+            ["admin", "realms", self.realm.as_str()]
+                .into_iter()
+                .chain(segments.iter().copied()),
+        )
+    }
+}
+
 /// high-level keycloak api client
 ///
 /// see also the extension traits in the [api] module for available methods
@@ -59,11 +98,38 @@ impl<A: AuthenticationProvider> Keycloak<A> {
             return Err(Error::new_kind(ErrorKind::MissingAccessToken));
         };
         let client = Self::build_client(access_token);
-        Ok(Self {
+        let keycloak = Self {
             config,
             auth: RwLock::new(auth),
             api_client: RwLock::new(self::rest::Client::new_with_client(base_url, client)),
-        })
+        };
+        Ok(keycloak)
+    }
+
+    /// check whether the server's major version matches the version of the api spec
+    ///
+    /// returns `None` if the crate was built from a spec that isn't a release version (e.g. the
+    /// default `unstable` spec). mismatches are logged as warning as well, they commonly show up
+    /// as deserialization errors later on. this queries the server info, which fails for accounts
+    /// without access to it (e.g. many service accounts).
+    pub async fn check_server_version(&self) -> Result<Option<bool>, crate::Error> {
+        let spec = self::rest::client_spec_info();
+        let Some(spec_major) = spec.major_version() else {
+            tracing::debug!(
+                "api spec version {} is not a release version, skipping version check",
+                spec.version
+            );
+            return Ok(None);
+        };
+        let server_version = self.server_info().await?.system_info.version;
+        let matches = self::rest::major_version(&server_version) == Some(spec_major);
+        if !matches {
+            tracing::warn!(
+                "keycloak server version {server_version} doesn't match api spec version {}",
+                spec.version
+            );
+        }
+        Ok(Some(matches))
     }
 
     pub fn config(&self) -> &KeycloakConfig {
@@ -118,12 +184,20 @@ impl<A: AuthenticationProvider> Keycloak<A> {
     /// execute a callback with the inner low-level api client
     /// this method also ensures that access tokens are refreshed if necessary
     ///
-    /// `async fn`s can be passed directly like this:
+    /// `async fn`s can be passed directly like this (the methods of the client return request
+    /// builders, so only the parameters actually needed have to be set):
     ///
     /// ```no_run
-    /// async fn callback(client: &keycloak_api::rest::Client) -> Result<(), keycloak_api::Error> {
-    ///     // do something with the client
-    ///     Ok(())
+    /// async fn callback(client: &keycloak_api::rest::Client) -> Result<usize, keycloak_api::Error> {
+    ///     let users = client
+    ///         .get_realm_users()
+    ///         .realm("master")
+    ///         .enabled(false)
+    ///         .max(10)
+    ///         .send()
+    ///         .await
+    ///         .map_err(keycloak_api::error::progenitor)?;
+    ///     Ok(users.len())
     /// }
     ///
     /// # async fn test() {
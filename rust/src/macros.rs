@@ -21,3 +21,23 @@ macro_rules! paginate_api {
         results
     }};
 }
+
+/// like `paginate_api!`, but only counts the results without keeping them in memory
+macro_rules! count_api {
+    (|$first:ident, $max:ident| {$api_call:expr}) => {{
+        const PAGE_MAX: i32 = 100;
+
+        let mut count = 0;
+        let $max = PAGE_MAX;
+        let mut page_offset = Some(0);
+        while let Some($first) = page_offset.take() {
+            let page_len = $api_call.len();
+            count += page_len;
+            // next page
+            if page_len == PAGE_MAX as usize {
+                page_offset = Some($first + PAGE_MAX);
+            }
+        }
+        count
+    }};
+}
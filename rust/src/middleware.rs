@@ -0,0 +1,405 @@
+//! policy enforcement for services built on tower (e.g. axum)
+//!
+//! [`EnforceLayer`] validates the bearer token of every incoming request using keycloak's token
+//! introspection endpoint and checks the roles granted to it. requests are rejected with
+//! `401 Unauthorized` for missing or invalid tokens and `403 Forbidden` for missing roles. the
+//! claims of accepted tokens are inserted into the request extensions as
+//! [`TokenClaims`](crate::rest::TokenClaims). only access tokens (`typ: Bearer`) are accepted, so
+//! e.g. refresh tokens can't be used as bearer credentials.
+//!
+//! by default, every request causes an introspection request to keycloak. to reduce the load,
+//! [`TokenValidator::cache`] keeps the results for a while, at the cost of accepting revoked
+//! tokens until their cache entry expires.
+//!
+//! ```no_run
+//! # fn test<S>(service: S) {
+//! use keycloak_api::{middleware::{EnforceLayer, RequiredRoles, TokenValidator}, KeycloakConfig};
+//! use tower_layer::Layer;
+//!
+//! let config = KeycloakConfig {
+//!     base_url: "http://localhost:8080".into(),
+//!     realm: "test".into(),
+//! };
+//! let validator = TokenValidator::new(&config, "my-service", Some("secret"));
+//! let roles = RequiredRoles::new().client_role("my-service", "reader");
+//! let service = EnforceLayer::new(validator, roles)
+//!     .audience("my-service")
+//!     .layer(service);
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::{rest::TokenClaims, KeycloakConfig};
+
+type Result<T, E = crate::Error> = std::result::Result<T, E>;
+
+/// validates access tokens using keycloak's token introspection endpoint
+pub struct TokenValidator {
+    config: KeycloakConfig,
+    client_id: String,
+    client_secret: Option<String>,
+    client: reqwest::Client,
+    cache: Option<IntrospectionCache>,
+}
+
+/// introspection results of active tokens, see [`TokenValidator::cache`]
+struct IntrospectionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (TokenClaims, SystemTime)>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    #[serde(flatten)]
+    claims: TokenClaims,
+}
+
+impl TokenValidator {
+    /// the client is used to authenticate to the introspection endpoint and should usually be the
+    /// client representing the protected service
+    pub fn new(config: &KeycloakConfig, client_id: &str, client_secret: Option<&str>) -> Self {
+        Self {
+            config: config.clone(),
+            client_id: client_id.into(),
+            client_secret: client_secret.map(Into::into),
+            client: reqwest::ClientBuilder::new()
+                .connect_timeout(Duration::from_secs(5))
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("BUG: reqwest client builder failed"),
+            cache: None,
+        }
+    }
+
+    /// cache the claims of active tokens for up to `ttl` (but never beyond their expiry)
+    ///
+    /// tokens revoked in keycloak (e.g. by logging out) are still accepted until their cache
+    /// entry expires
+    pub fn cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(IntrospectionCache {
+            ttl,
+            entries: Mutex::default(),
+        });
+        self
+    }
+
+    /// introspect an access token
+    ///
+    /// returns `None` if the token is not active (expired, revoked or invalid) or not an access
+    /// token
+    #[tracing::instrument(skip_all)]
+    pub async fn introspect(&self, access_token: &str) -> Result<Option<TokenClaims>> {
+        if let Some(cache) = &self.cache {
+            let entries = cache.entries.lock().expect("BUG: cache lock poisoned");
+            if let Some((claims, valid_until)) = entries.get(access_token) {
+                if *valid_until > SystemTime::now() {
+                    return Ok(Some(claims.clone()));
+                }
+            }
+        }
+
+        let url = format!(
+            "{}/realms/{}/protocol/openid-connect/token/introspect",
+            self.config.base_url, self.config.realm
+        );
+        let response = self
+            .client
+            .post(url)
+            .basic_auth(&self.client_id, self.client_secret.as_ref())
+            .form(&[("token", access_token)])
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        let introspection: IntrospectionResponse =
+            serde_json::from_slice(&bytes).map_err(crate::error::deserialize)?;
+        if !introspection.active {
+            tracing::debug!("token is not active");
+            return Ok(None);
+        }
+        if introspection.claims.typ.as_deref() != Some("Bearer") {
+            tracing::debug!(
+                typ = introspection.claims.typ,
+                "token is not an access token"
+            );
+            return Ok(None);
+        }
+
+        if let Some(cache) = &self.cache {
+            let now = SystemTime::now();
+            let valid_until = cache_until(now, cache.ttl, introspection.claims.exp);
+            let mut entries = cache.entries.lock().expect("BUG: cache lock poisoned");
+            entries.retain(|_, (_, valid_until)| *valid_until > now);
+            entries.insert(
+                access_token.into(),
+                (introspection.claims.clone(), valid_until),
+            );
+        }
+        Ok(Some(introspection.claims))
+    }
+}
+
+/// end of the caching period of a token expiring at `exp` (seconds since the unix epoch)
+fn cache_until(now: SystemTime, ttl: Duration, exp: Option<u64>) -> SystemTime {
+    let until = now + ttl;
+    match exp {
+        Some(exp) => until.min(UNIX_EPOCH + Duration::from_secs(exp)),
+        None => until,
+    }
+}
+
+/// roles a token must have been granted to be accepted
+#[derive(Debug, Clone, Default)]
+pub struct RequiredRoles {
+    realm_roles: Vec<String>,
+    client_roles: Vec<(String, String)>,
+}
+
+impl RequiredRoles {
+    /// no roles required, any active token is accepted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// require a realm role
+    pub fn realm_role(mut self, role: &str) -> Self {
+        self.realm_roles.push(role.into());
+        self
+    }
+
+    /// require a role of the client with the given client id
+    pub fn client_role(mut self, client_id: &str, role: &str) -> Self {
+        self.client_roles.push((client_id.into(), role.into()));
+        self
+    }
+
+    /// check whether all required roles are granted by the given claims
+    pub fn satisfied_by(&self, claims: &TokenClaims) -> bool {
+        let realm_roles = &claims.realm_access.roles;
+        if !self.realm_roles.iter().all(|r| realm_roles.contains(r)) {
+            return false;
+        }
+        self.client_roles.iter().all(|(client_id, role)| {
+            claims
+                .resource_access
+                .get(client_id)
+                .is_some_and(|access| access.roles.contains(role))
+        })
+    }
+}
+
+/// [`Layer`](tower_layer::Layer) enforcing valid tokens with the required roles
+#[derive(Clone)]
+pub struct EnforceLayer {
+    validator: Arc<TokenValidator>,
+    roles: Arc<RequiredRoles>,
+    audience: Option<Arc<str>>,
+}
+
+impl EnforceLayer {
+    pub fn new(validator: TokenValidator, roles: RequiredRoles) -> Self {
+        Self {
+            validator: Arc::new(validator),
+            roles: Arc::new(roles),
+            audience: None,
+        }
+    }
+
+    /// only accept tokens issued for the given client id, i.e. tokens with the client id in
+    /// their `aud` claim or issued to the client itself (`azp`)
+    pub fn audience(mut self, client_id: &str) -> Self {
+        self.audience = Some(client_id.into());
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for EnforceLayer {
+    type Service = Enforce<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Enforce {
+            inner,
+            validator: self.validator.clone(),
+            roles: self.roles.clone(),
+            audience: self.audience.clone(),
+        }
+    }
+}
+
+/// service created by [`EnforceLayer`]
+#[derive(Clone)]
+pub struct Enforce<S> {
+    inner: S,
+    validator: Arc<TokenValidator>,
+    roles: Arc<RequiredRoles>,
+    audience: Option<Arc<str>>,
+}
+
+impl<S, ReqBody, ResBody> tower_service::Service<http::Request<ReqBody>> for Enforce<S>
+where
+    S: tower_service::Service<http::Request<ReqBody>, Response = http::Response<ResBody>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        // the inner service was polled ready, so use it and leave the clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let validator = self.validator.clone();
+        let roles = self.roles.clone();
+        let audience = self.audience.clone();
+
+        Box::pin(async move {
+            let Some(token) = bearer_token(&request) else {
+                return Ok(reject(StatusCode::UNAUTHORIZED));
+            };
+            let claims = match validator.introspect(token).await {
+                Ok(Some(claims)) => claims,
+                Ok(None) => return Ok(reject(StatusCode::UNAUTHORIZED)),
+                Err(e) => {
+                    tracing::warn!("token introspection failed: {e}");
+                    return Ok(reject(StatusCode::SERVICE_UNAVAILABLE));
+                }
+            };
+            if let Some(audience) = &audience {
+                if !has_audience(&claims, audience) {
+                    tracing::debug!("token was issued for a different audience");
+                    return Ok(reject(StatusCode::UNAUTHORIZED));
+                }
+            }
+            if !roles.satisfied_by(&claims) {
+                tracing::debug!("token is missing required roles");
+                return Ok(reject(StatusCode::FORBIDDEN));
+            }
+            request.extensions_mut().insert(claims);
+            inner.call(request).await
+        })
+    }
+}
+
+fn bearer_token<B>(request: &http::Request<B>) -> Option<&str> {
+    let header = request
+        .headers()
+        .get(http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    let (scheme, token) = header.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then_some(token.trim())
+}
+
+fn has_audience(claims: &TokenClaims, audience: &str) -> bool {
+    claims.aud.iter().any(|aud| aud == audience) || claims.azp.as_deref() == Some(audience)
+}
+
+fn reject<B: Default>(status: StatusCode) -> http::Response<B> {
+    let mut response = http::Response::new(B::default());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::rest::TokenClaims;
+
+    fn claims(json: serde_json::Value) -> TokenClaims {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let request = |header: Option<&str>| {
+            let mut request = http::Request::builder();
+            if let Some(header) = header {
+                request = request.header(http::header::AUTHORIZATION, header);
+            }
+            request.body(()).unwrap()
+        };
+        assert_eq!(
+            super::bearer_token(&request(Some("Bearer abc"))),
+            Some("abc")
+        );
+        assert_eq!(
+            super::bearer_token(&request(Some("bearer  abc "))),
+            Some("abc")
+        );
+        assert_eq!(super::bearer_token(&request(Some("Basic abc"))), None);
+        assert_eq!(super::bearer_token(&request(Some("Bearer"))), None);
+        assert_eq!(super::bearer_token(&request(None)), None);
+    }
+
+    #[test]
+    fn test_required_roles() {
+        let claims = claims(serde_json::json!({
+            "realm_access": { "roles": ["user"] },
+            "resource_access": { "app": { "roles": ["reader"] } },
+        }));
+        assert!(super::RequiredRoles::new().satisfied_by(&claims));
+        assert!(super::RequiredRoles::new()
+            .realm_role("user")
+            .client_role("app", "reader")
+            .satisfied_by(&claims));
+        assert!(!super::RequiredRoles::new()
+            .realm_role("admin")
+            .satisfied_by(&claims));
+        assert!(!super::RequiredRoles::new()
+            .client_role("app", "writer")
+            .satisfied_by(&claims));
+        assert!(!super::RequiredRoles::new()
+            .client_role("other", "reader")
+            .satisfied_by(&claims));
+    }
+
+    #[test]
+    fn test_has_audience() {
+        let single = claims(serde_json::json!({ "aud": "app", "azp": "frontend" }));
+        assert!(super::has_audience(&single, "app"));
+        assert!(super::has_audience(&single, "frontend"));
+        assert!(!super::has_audience(&single, "other"));
+
+        let multiple = claims(serde_json::json!({ "aud": ["account", "app"] }));
+        assert!(super::has_audience(&multiple, "app"));
+        assert!(!super::has_audience(&multiple, "other"));
+    }
+
+    #[test]
+    fn test_cache_until() {
+        let now = UNIX_EPOCH + Duration::from_secs(1000);
+        let ttl = Duration::from_secs(60);
+        assert_eq!(super::cache_until(now, ttl, None), now + ttl);
+        assert_eq!(super::cache_until(now, ttl, Some(2000)), now + ttl);
+        assert_eq!(
+            super::cache_until(now, ttl, Some(1030)),
+            UNIX_EPOCH + Duration::from_secs(1030)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! client for keycloak's client registration service
+//!
+//! the client registration service is separate from the admin api: it lives below the realm's
+//! public endpoints and is authenticated with initial access tokens (see
+//! [`create_initial_access_token`](crate::api::KeycloakRealmExt::create_initial_access_token))
+//! and the registration access tokens returned for each registered client instead of an admin
+//! user's access token.
+//!
+//! every successful create, get and update returns a new registration access token, which has to
+//! be used for the next request concerning the client.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::ResourceType, rest::types::ClientRepresentation, Error, ErrorKind, KeycloakConfig,
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// client metadata as used by the `openid-connect` provider (rfc 7591)
+///
+/// unset fields are omitted, fields not covered here end up in `extra`. the client secret and
+/// the registration access token are redacted in the debug output.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct OidcClientMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    /// unix timestamp of the client's registration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id_issued_at: Option<i64>,
+    /// unix timestamp after which the secret expires (0 if it doesn't expire)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret_expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_client_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirect_uris: Vec<String>,
+    /// e.g. `client_secret_basic` or `none` for public clients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_endpoint_auth_method: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_types: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// space separated list of scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contacts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for OidcClientMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "<redacted>");
+        f.debug_struct("OidcClientMetadata")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &redacted(&self.client_secret))
+            .field("client_id_issued_at", &self.client_id_issued_at)
+            .field("client_secret_expires_at", &self.client_secret_expires_at)
+            .field(
+                "registration_access_token",
+                &redacted(&self.registration_access_token),
+            )
+            .field("registration_client_uri", &self.registration_client_uri)
+            .field("redirect_uris", &self.redirect_uris)
+            .field(
+                "token_endpoint_auth_method",
+                &self.token_endpoint_auth_method,
+            )
+            .field("grant_types", &self.grant_types)
+            .field("response_types", &self.response_types)
+            .field("client_name", &self.client_name)
+            .field("client_uri", &self.client_uri)
+            .field("logo_uri", &self.logo_uri)
+            .field("scope", &self.scope)
+            .field("contacts", &self.contacts)
+            .field("jwks_uri", &self.jwks_uri)
+            .field("extra", &self.extra)
+            .finish()
+    }
+}
+
+/// client for the client registration service of a realm
+pub struct ClientRegistration {
+    config: KeycloakConfig,
+    client: reqwest::Client,
+}
+
+impl ClientRegistration {
+    pub fn new(config: &KeycloakConfig) -> Self {
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("BUG: reqwest client builder failed");
+        Self {
+            config: config.clone(),
+            client,
+        }
+    }
+
+    /// register a client using keycloak's own client representation
+    ///
+    /// `token` is an initial access token or a bearer token of a user allowed to create clients.
+    /// it can be omitted if the realm's client registration policies allow anonymous
+    /// registration.
+    #[tracing::instrument(skip_all, fields(client_id = client.client_id))]
+    pub async fn create(
+        &self,
+        token: Option<&str>,
+        client: &ClientRepresentation,
+    ) -> Result<ClientRepresentation> {
+        self.create_with("default", token, client).await
+    }
+
+    /// get a client registered using keycloak's own client representation
+    #[tracing::instrument(skip(self, registration_access_token))]
+    pub async fn get(
+        &self,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<ClientRepresentation> {
+        self.get_with("default", client_id, registration_access_token)
+            .await
+    }
+
+    /// update a client registered using keycloak's own client representation
+    ///
+    /// this replaces the whole client, unset fields are reset to their defaults
+    #[tracing::instrument(skip(self, registration_access_token, client))]
+    pub async fn update(
+        &self,
+        client_id: &str,
+        registration_access_token: &str,
+        client: &ClientRepresentation,
+    ) -> Result<ClientRepresentation> {
+        self.update_with("default", client_id, registration_access_token, client)
+            .await
+    }
+
+    /// delete a client registered using keycloak's own client representation
+    #[tracing::instrument(skip(self, registration_access_token))]
+    pub async fn delete(&self, client_id: &str, registration_access_token: &str) -> Result<()> {
+        self.delete_with("default", client_id, registration_access_token)
+            .await
+    }
+
+    /// register a client using openid connect dynamic client registration
+    ///
+    /// see [`create`](Self::create) for `token`
+    #[tracing::instrument(skip_all, fields(client_name = client.client_name))]
+    pub async fn oidc_create(
+        &self,
+        token: Option<&str>,
+        client: &OidcClientMetadata,
+    ) -> Result<OidcClientMetadata> {
+        self.create_with("openid-connect", token, client).await
+    }
+
+    /// get a client registered using openid connect dynamic client registration
+    #[tracing::instrument(skip(self, registration_access_token))]
+    pub async fn oidc_get(
+        &self,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<OidcClientMetadata> {
+        self.get_with("openid-connect", client_id, registration_access_token)
+            .await
+    }
+
+    /// update a client registered using openid connect dynamic client registration
+    ///
+    /// this replaces the whole client, unset fields are reset to their defaults
+    #[tracing::instrument(skip(self, registration_access_token, client))]
+    pub async fn oidc_update(
+        &self,
+        client_id: &str,
+        registration_access_token: &str,
+        client: &OidcClientMetadata,
+    ) -> Result<OidcClientMetadata> {
+        self.update_with(
+            "openid-connect",
+            client_id,
+            registration_access_token,
+            client,
+        )
+        .await
+    }
+
+    /// delete a client registered using openid connect dynamic client registration
+    #[tracing::instrument(skip(self, registration_access_token))]
+    pub async fn oidc_delete(
+        &self,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<()> {
+        self.delete_with("openid-connect", client_id, registration_access_token)
+            .await
+    }
+
+    fn url(&self, provider: &str) -> Result<reqwest::Url> {
+        self.config.url([
+            "realms",
+            &self.config.realm,
+            "clients-registrations",
+            provider,
+        ])
+    }
+
+    fn client_url(&self, provider: &str, client_id: &str) -> Result<reqwest::Url> {
+        self.config.url([
+            "realms",
+            &self.config.realm,
+            "clients-registrations",
+            provider,
+            client_id,
+        ])
+    }
+
+    async fn create_with<T: Serialize + DeserializeOwned>(
+        &self,
+        provider: &str,
+        token: Option<&str>,
+        client: &T,
+    ) -> Result<T> {
+        tracing::debug!("registering client");
+        let mut request = self.client.post(self.url(provider)?).json(client);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await.map_status(
+                StatusCode::CONFLICT,
+                ErrorKind::Conflict(ResourceType::Client),
+            ));
+        }
+        json_response(response).await
+    }
+
+    async fn get_with<T: DeserializeOwned>(
+        &self,
+        provider: &str,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<T> {
+        tracing::debug!("querying registered client");
+        let response = self
+            .client
+            .get(self.client_url(provider, client_id)?)
+            .bearer_auth(registration_access_token)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(not_found(crate::error::error_response(response).await));
+        }
+        json_response(response).await
+    }
+
+    async fn update_with<T: Serialize + DeserializeOwned>(
+        &self,
+        provider: &str,
+        client_id: &str,
+        registration_access_token: &str,
+        client: &T,
+    ) -> Result<T> {
+        tracing::debug!("updating registered client");
+        let response = self
+            .client
+            .put(self.client_url(provider, client_id)?)
+            .bearer_auth(registration_access_token)
+            .json(client)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(not_found(crate::error::error_response(response).await));
+        }
+        json_response(response).await
+    }
+
+    async fn delete_with(
+        &self,
+        provider: &str,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<()> {
+        tracing::debug!("deleting registered client");
+        let response = self
+            .client
+            .delete(self.client_url(provider, client_id)?)
+            .bearer_auth(registration_access_token)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(not_found(crate::error::error_response(response).await));
+        }
+        Ok(())
+    }
+}
+
+fn not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::Client),
+    )
+}
+
+async fn json_response<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+    serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_oidc_client_metadata() {
+        let metadata: super::OidcClientMetadata = serde_json::from_value(serde_json::json!({
+            "client_id": "abc",
+            "redirect_uris": ["https://example.com/callback"],
+            "tls_client_certificate_bound_access_tokens": false,
+        }))
+        .unwrap();
+        assert_eq!(metadata.client_id.as_deref(), Some("abc"));
+        assert_eq!(metadata.redirect_uris, ["https://example.com/callback"]);
+        assert_eq!(
+            metadata.extra["tls_client_certificate_bound_access_tokens"],
+            false
+        );
+
+        let serialized = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "client_id": "abc",
+                "redirect_uris": ["https://example.com/callback"],
+                "tls_client_certificate_bound_access_tokens": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_oidc_client_metadata_debug() {
+        let metadata = super::OidcClientMetadata {
+            client_id: Some("abc".into()),
+            client_secret: Some("hunter2".into()),
+            registration_access_token: Some("eyJhbGciOi".into()),
+            ..Default::default()
+        };
+        let debug = format!("{metadata:?}");
+        assert!(debug.contains(r#"client_id: Some("abc")"#));
+        assert!(debug.contains(r#"client_secret: Some("<redacted>")"#));
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("eyJhbGciOi"));
+    }
+}
@@ -1,16 +1,60 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 // re-export the generated rest client
 pub use self::generated::Client;
 
+/// request builders returned by the methods of [`Client`]
+pub mod builder {
+    pub use super::generated::builder::*;
+}
+
 /// types used in the api
 pub mod types;
 
-#[allow(dead_code, clippy::all, clippy::pedantic, clippy::nursery)]
+// generated code, which lints are triggered depends on the progenitor and rustc versions
+#[allow(warnings, clippy::all, clippy::pedantic, clippy::nursery)]
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/keycloak-api-gen.rs"));
 }
 
+/// information about the openapi spec the low-level [`Client`] was generated from
+#[derive(Debug, Clone, Copy)]
+pub struct SpecInfo {
+    /// keycloak version of the spec
+    ///
+    /// this is `unstable` for the spec bundled with this crate and `unknown` for custom specs
+    /// without `KEYCLOAK_VERSION` set at build time
+    pub version: &'static str,
+    /// keycloak git revision the spec was built from (set via `KEYCLOAK_REVISION` at build time)
+    pub revision: Option<&'static str>,
+    /// unix timestamp of the client generation
+    pub generated_at: u64,
+}
+
+impl SpecInfo {
+    /// major version of the spec, if the version is an actual release version
+    pub fn major_version(&self) -> Option<u32> {
+        major_version(self.version)
+    }
+}
+
+/// get information about the openapi spec the low-level [`Client`] was generated from
+pub fn client_spec_info() -> SpecInfo {
+    SpecInfo {
+        version: env!("KEYCLOAK_API_SPEC_VERSION"),
+        revision: option_env!("KEYCLOAK_API_SPEC_REVISION"),
+        generated_at: env!("KEYCLOAK_API_GENERATED_AT")
+            .parse()
+            .expect("BUG: build script emitted invalid timestamp"),
+    }
+}
+
+pub(crate) fn major_version(version: &str) -> Option<u32> {
+    version.split('.').next()?.parse().ok()
+}
+
 #[derive(Debug, Serialize)]
 pub struct TokenRequest<'a> {
     client_id: &'a str,
@@ -68,9 +112,288 @@ pub struct TokenResponse {
     pub session_state: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// (subset of the) claims of a keycloak access token
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenClaims {
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    /// type of the token, `Bearer` for access tokens
+    pub typ: Option<String>,
+    /// audiences of the token (keycloak omits the array for a single audience)
+    #[serde(default, deserialize_with = "string_or_vec")]
+    pub aud: Vec<String>,
+    pub azp: Option<String>,
+    pub preferred_username: Option<String>,
+    pub exp: Option<u64>,
+    #[serde(default)]
+    pub realm_access: TokenRoles,
+    #[serde(default)]
+    pub resource_access: HashMap<String, TokenRoles>,
+}
+
+fn string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        String(String),
+        Vec(Vec<String>),
+    }
+
+    Ok(match StringOrVec::deserialize(deserializer)? {
+        StringOrVec::String(value) => vec![value],
+        StringOrVec::Vec(values) => values,
+    })
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenRoles {
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// the principal used to access the admin api, see [`whoami`](crate::api::KeycloakRealmExt::whoami)
+#[derive(Debug, Clone)]
+pub struct WhoAmI {
+    /// claims of the current access token
+    pub claims: TokenClaims,
+    /// effective roles of the `realm-management` client (`master-realm` in the master realm)
+    ///
+    /// this is `None` if the principal isn't allowed to view its own role mappings
+    pub management_roles: Option<Vec<types::RoleRepresentation>>,
+}
+
+/// brute force detection status of a user
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BruteForceStatus {
+    /// whether the user is currently locked because of too many login failures
+    pub disabled: bool,
+    pub num_failures: u32,
+    /// unix timestamp (in milliseconds) of the last login failure
+    pub last_failure: i64,
+    /// ip address of the last login failure
+    #[serde(rename = "lastIPFailure")]
+    pub last_ip_failure: Option<String>,
+}
+
+/// kind of a user storage (federation) synchronization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// synchronize all users
+    Full,
+    /// only synchronize users changed since the last synchronization
+    ChangedUsers,
+}
+
+impl SyncAction {
+    pub(crate) fn as_query(self) -> &'static str {
+        match self {
+            Self::Full => "triggerFullSync",
+            Self::ChangedUsers => "triggerChangedUsersSync",
+        }
+    }
+}
+
+/// result of a user storage synchronization
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SynchronizationResult {
+    pub ignored: bool,
+    pub added: u32,
+    pub updated: u32,
+    pub removed: u32,
+    pub failed: u32,
+    /// human readable summary
+    pub status: Option<String>,
+}
+
+/// number of sessions of a client, see
+/// [`client_session_stats`](crate::api::KeycloakRealmExt::client_session_stats)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSessionStats {
+    pub id: String,
+    pub client_id: String,
+    /// number of active (online) sessions
+    pub active: u64,
+    /// number of offline sessions
+    pub offline: u64,
+}
+
+impl TryFrom<HashMap<String, String>> for ClientSessionStats {
+    type Error = crate::Error;
+
+    // keycloak returns the counts as strings
+    fn try_from(mut stats: HashMap<String, String>) -> Result<Self, Self::Error> {
+        let mut field = |name: &str| {
+            stats
+                .remove(name)
+                .ok_or_else(|| crate::Error::new_kind(crate::ErrorKind::MissingField(name.into())))
+        };
+        let id = field("id")?;
+        let client_id = field("clientId")?;
+        let count = |value: String| {
+            value.parse().map_err(|_| {
+                crate::Error::new_kind(crate::ErrorKind::WrongType("number".into(), value))
+            })
+        };
+        let active = count(field("active")?)?;
+        let offline = count(field("offline")?)?;
+        Ok(Self {
+            id,
+            client_id,
+            active,
+            offline,
+        })
+    }
+}
+
+/// requirement of an execution within its authentication flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionRequirement {
+    /// the execution must succeed
+    Required,
+    /// one of the alternative executions of the flow must succeed
+    Alternative,
+    /// the execution is skipped
+    Disabled,
+    /// the sub-flow is only executed if its conditions are met
+    Conditional,
+}
+
+impl ExecutionRequirement {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Required => "REQUIRED",
+            Self::Alternative => "ALTERNATIVE",
+            Self::Disabled => "DISABLED",
+            Self::Conditional => "CONDITIONAL",
+        }
+    }
+}
+
+/// type of a sub-flow added to an authentication flow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowType {
+    /// a generic flow of executions
+    Basic,
+    /// a flow of form actions rendered by a form authenticator (e.g. `registration-page-form`)
+    Form { provider: String },
+}
+
+/// how a partial import handles resources that already exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportPolicy {
+    /// keep the existing resource
+    Skip,
+    /// replace the existing resource
+    Overwrite,
+    /// abort the whole import
+    Fail,
+}
+
+/// request body of the realm's partial import endpoint
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
+pub struct PartialImportRepresentation {
+    pub if_resource_exists: ImportPolicy,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<types::UserRepresentation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<types::GroupRepresentation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clients: Vec<types::ClientRepresentation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub identity_providers: Vec<types::IdentityProviderRepresentation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<types::RolesRepresentation>,
+}
+
+impl PartialImportRepresentation {
+    /// an empty import using the given policy
+    pub fn new(if_resource_exists: ImportPolicy) -> Self {
+        Self {
+            if_resource_exists,
+            users: Vec::new(),
+            groups: Vec::new(),
+            clients: Vec::new(),
+            identity_providers: Vec::new(),
+            roles: None,
+        }
+    }
+}
+
+/// result of a partial import
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialImportResults {
+    pub added: u32,
+    pub skipped: u32,
+    pub overwritten: u32,
+    /// result per imported resource
+    #[serde(default)]
+    pub results: Vec<PartialImportResult>,
+}
+
+impl PartialImportResults {
+    /// merge the results of another import into these
+    pub fn extend(&mut self, other: Self) {
+        self.added += other.added;
+        self.skipped += other.skipped;
+        self.overwritten += other.overwritten;
+        self.results.extend(other.results);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialImportResult {
+    pub action: ImportAction,
+    /// e.g. `USER` or `GROUP`
+    pub resource_type: String,
+    pub resource_name: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportAction {
+    Added,
+    Skipped,
+    Overwritten,
+}
 
+/// a client secret
+///
+/// the secret is redacted in the debug output, so it doesn't end up in logs by accident
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClientSecret(String);
+
+impl ClientSecret {
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl std::fmt::Debug for ClientSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ClientSecret(<redacted>)")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ServerInfo {
     pub system_info: ServerInfoSystemInfo,
 }
@@ -93,6 +416,37 @@ pub struct ServerInfoSystemInfo {
 
 #[cfg(test)]
 mod test {
+    #[test]
+    fn test_major_version() {
+        assert_eq!(super::major_version("25.0.1"), Some(25));
+        assert_eq!(super::major_version("26"), Some(26));
+        assert_eq!(super::major_version("unstable"), None);
+        assert_eq!(super::major_version("999.0.0-SNAPSHOT"), Some(999));
+    }
+
+    #[test]
+    fn test_client_session_stats() {
+        let stats = std::collections::HashMap::from([
+            ("id".to_string(), "1234".to_string()),
+            ("clientId".to_string(), "app".to_string()),
+            ("active".to_string(), "3".to_string()),
+            ("offline".to_string(), "0".to_string()),
+        ]);
+        let stats = super::ClientSessionStats::try_from(stats).unwrap();
+        assert_eq!(stats.client_id, "app");
+        assert_eq!((stats.active, stats.offline), (3, 0));
+
+        let stats = std::collections::HashMap::from([("id".to_string(), "1234".to_string())]);
+        assert!(super::ClientSessionStats::try_from(stats).is_err());
+    }
+
+    #[test]
+    fn test_client_secret_debug() {
+        let secret = super::ClientSecret::new("hunter2".into());
+        assert_eq!(format!("{secret:?}"), "ClientSecret(<redacted>)");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
     #[test]
     fn test_token_request() {
         let request = super::TokenRequest::new_password("id", None, "user", "pass");
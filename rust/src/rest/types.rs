@@ -5,6 +5,7 @@ pub use self::generated::*;
 
 // re-export custom types
 // explicit re-export for `self::policies::ClientPolicyRepresentation` as that's also part of the generated types
+pub use self::attributes::{Attributes, HasAttributes};
 pub use self::policies::{ClientPolicyRepresentation, *};
 
 /// types generated from the keycloak openapi spec
@@ -15,3 +16,6 @@ pub mod generated {
 
 /// concrete subtypes of [`PolicyRepresentation`]
 pub mod policies;
+
+/// typed access to attributes
+mod attributes;
@@ -0,0 +1,174 @@
+use std::borrow::{Borrow, BorrowMut};
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{rest::types::*, Error, ErrorKind};
+
+type AttributeMap = HashMap<String, Vec<String>>;
+
+/// typed access to the attributes of users, groups and roles
+///
+/// keycloak stores every attribute as a list of strings. the typed getters use the first value of
+/// an attribute and the typed setters replace all values with a single one.
+#[derive(Debug, Clone)]
+pub struct Attributes<M>(M);
+
+impl<M: Borrow<AttributeMap>> Attributes<M> {
+    pub fn new(map: M) -> Self {
+        Self(map)
+    }
+
+    /// get the inner attribute map
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.borrow().contains_key(key)
+    }
+
+    /// get all values of an attribute
+    pub fn get_all(&self, key: &str) -> &[String] {
+        self.0
+            .borrow()
+            .get(key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get_all(key).first().map(String::as_str)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, Error> {
+        self.get_parsed(key, "bool")
+    }
+
+    pub fn get_i64(&self, key: &str) -> Result<Option<i64>, Error> {
+        self.get_parsed(key, "i64")
+    }
+
+    /// get an attribute containing a json document
+    pub fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.get_str(key)
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(crate::error::deserialize)
+    }
+
+    fn get_parsed<T: std::str::FromStr>(
+        &self,
+        key: &str,
+        type_name: &str,
+    ) -> Result<Option<T>, Error> {
+        let Some(value) = self.get_str(key) else {
+            return Ok(None);
+        };
+        value
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::new_kind(ErrorKind::WrongType(type_name.into(), value.into())))
+    }
+}
+
+impl<M: BorrowMut<AttributeMap>> Attributes<M> {
+    /// replace all values of an attribute
+    pub fn set_all<I, S>(&mut self, key: &str, values: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        self.0.borrow_mut().insert(key.into(), values);
+    }
+
+    pub fn set_str(&mut self, key: &str, value: &str) {
+        self.set_all(key, [value]);
+    }
+
+    pub fn set_bool(&mut self, key: &str, value: bool) {
+        self.set_all(key, [value.to_string()]);
+    }
+
+    pub fn set_i64(&mut self, key: &str, value: i64) {
+        self.set_all(key, [value.to_string()]);
+    }
+
+    /// store a value as json document
+    pub fn set_json<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        let value = serde_json::to_string(value).map_err(crate::error::deserialize)?;
+        self.set_all(key, [value]);
+        Ok(())
+    }
+
+    /// remove an attribute, returning its previous values
+    pub fn remove(&mut self, key: &str) -> Vec<String> {
+        self.0.borrow_mut().remove(key).unwrap_or_default()
+    }
+}
+
+/// representations with attributes, see [`Attributes`]
+pub trait HasAttributes {
+    fn attribute_map(&self) -> &AttributeMap;
+
+    fn attribute_map_mut(&mut self) -> &mut AttributeMap;
+
+    fn attrs(&self) -> Attributes<&AttributeMap> {
+        Attributes(self.attribute_map())
+    }
+
+    fn attrs_mut(&mut self) -> Attributes<&mut AttributeMap> {
+        Attributes(self.attribute_map_mut())
+    }
+}
+
+macro_rules! impl_has_attributes {
+    ($($type:ty),*) => {
+        $(
+            impl HasAttributes for $type {
+                fn attribute_map(&self) -> &AttributeMap {
+                    &self.attributes
+                }
+
+                fn attribute_map_mut(&mut self) -> &mut AttributeMap {
+                    &mut self.attributes
+                }
+            }
+        )*
+    };
+}
+
+impl_has_attributes!(UserRepresentation, GroupRepresentation, RoleRepresentation);
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::Attributes;
+
+    #[test]
+    fn test_attributes() {
+        let mut attributes = Attributes::new(HashMap::new());
+        attributes.set_str("department", "it");
+        attributes.set_bool("external", true);
+        attributes.set_i64("employee_number", 42);
+        attributes.set_json("settings", &vec![1, 2]).unwrap();
+        attributes.set_all("locations", ["berlin", "leipzig"]);
+
+        assert_eq!(attributes.get_str("department"), Some("it"));
+        assert_eq!(attributes.get_bool("external").unwrap(), Some(true));
+        assert_eq!(attributes.get_i64("employee_number").unwrap(), Some(42));
+        assert_eq!(
+            attributes.get_json::<Vec<i32>>("settings").unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(attributes.get_str("locations"), Some("berlin"));
+        assert_eq!(attributes.get_all("locations").len(), 2);
+        assert_eq!(attributes.get_i64("missing").unwrap(), None);
+        assert!(attributes.get_i64("department").is_err());
+
+        assert_eq!(attributes.remove("department"), ["it"]);
+        assert!(!attributes.contains("department"));
+    }
+}
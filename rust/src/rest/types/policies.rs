@@ -2,6 +2,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{rest::types::*, Error, ErrorKind};
 
+pub use self::builders::*;
+
+/// fluent builders for the policy types
+mod builders;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AggregatePolicyRepresentation {
@@ -43,7 +48,7 @@ pub struct GroupPolicyRepresentationGroupDefinition {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupPolicyRepresentation {
-    pub groups: Vec<RolePolicyRepresentationRoleDefinition>,
+    pub groups: Vec<GroupPolicyRepresentationGroupDefinition>,
     pub groups_claim: String,
     #[serde(flatten)]
     pub policy: PolicyRepresentation,
@@ -91,6 +96,111 @@ pub struct UserPolicyRepresentation {
     pub policy: PolicyRepresentation,
 }
 
+/// time-based policy
+///
+/// access is granted if the current time is within all configured ranges. `not_before` and
+/// `not_on_or_after` use the format `yyyy-MM-dd HH:mm:ss`, the other ranges are inclusive
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimePolicyRepresentation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_on_or_after: Option<String>,
+    /// day of the month (1-31)
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub day_month: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub day_month_end: Option<u32>,
+    /// month (1-12)
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub month: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub month_end: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub year: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub year_end: Option<u32>,
+    /// hour of the day (0-23)
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hour: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hour_end: Option<u32>,
+    /// minute of the hour (0-59)
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub minute: Option<u32>,
+    #[serde(
+        default,
+        with = "string_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub minute_end: Option<u32>,
+    #[serde(flatten)]
+    pub policy: PolicyRepresentation,
+}
+
+/// resource-based permission
+///
+/// the permission applies to the resources in `policy.resources`, or to all resources of
+/// `resource_type` if set. resources and policies can be given by id or name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourcePermissionRepresentation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(flatten)]
+    pub policy: PolicyRepresentation,
+}
+
+/// scope-based permission
+///
+/// the permission applies to the scopes in `policy.scopes`, optionally restricted to the
+/// resources in `policy.resources`. scopes, resources and policies can be given by id or name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopePermissionRepresentation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(flatten)]
+    pub policy: PolicyRepresentation,
+}
+
 impl TryFrom<PolicyRepresentation> for AggregatePolicyRepresentation {
     type Error = Error;
 
@@ -229,6 +339,348 @@ impl TryFrom<PolicyRepresentation> for UserPolicyRepresentation {
     }
 }
 
+impl TryFrom<PolicyRepresentation> for ResourcePermissionRepresentation {
+    type Error = Error;
+
+    fn try_from(mut value: PolicyRepresentation) -> Result<Self, Self::Error> {
+        check_policy_type(&value, "resource")?;
+
+        let resource_type = value.config.remove("defaultResourceType");
+
+        check_policy_config(&mut value, "resource");
+
+        Ok(Self {
+            resource_type,
+            policy: value,
+        })
+    }
+}
+
+impl TryFrom<PolicyRepresentation> for ScopePermissionRepresentation {
+    type Error = Error;
+
+    fn try_from(mut value: PolicyRepresentation) -> Result<Self, Self::Error> {
+        check_policy_type(&value, "scope")?;
+
+        let resource_type = value.config.remove("defaultResourceType");
+
+        check_policy_config(&mut value, "scope");
+
+        Ok(Self {
+            resource_type,
+            policy: value,
+        })
+    }
+}
+
+impl TryFrom<PolicyRepresentation> for TimePolicyRepresentation {
+    type Error = Error;
+
+    fn try_from(mut value: PolicyRepresentation) -> Result<Self, Self::Error> {
+        check_policy_type(&value, "time")?;
+
+        let not_before = value.config.remove("nbf");
+        let not_on_or_after = value.config.remove("noa");
+        let day_month = parse_policy_config_field(&mut value, "dayMonth")?;
+        let day_month_end = parse_policy_config_field(&mut value, "dayMonthEnd")?;
+        let month = parse_policy_config_field(&mut value, "month")?;
+        let month_end = parse_policy_config_field(&mut value, "monthEnd")?;
+        let year = parse_policy_config_field(&mut value, "year")?;
+        let year_end = parse_policy_config_field(&mut value, "yearEnd")?;
+        let hour = parse_policy_config_field(&mut value, "hour")?;
+        let hour_end = parse_policy_config_field(&mut value, "hourEnd")?;
+        let minute = parse_policy_config_field(&mut value, "minute")?;
+        let minute_end = parse_policy_config_field(&mut value, "minuteEnd")?;
+
+        check_policy_config(&mut value, "time");
+
+        Ok(Self {
+            not_before,
+            not_on_or_after,
+            day_month,
+            day_month_end,
+            month,
+            month_end,
+            year,
+            year_end,
+            hour,
+            hour_end,
+            minute,
+            minute_end,
+            policy: value,
+        })
+    }
+}
+
+impl From<AggregatePolicyRepresentation> for PolicyRepresentation {
+    fn from(value: AggregatePolicyRepresentation) -> Self {
+        into_policy(value.policy, "aggregate")
+    }
+}
+
+impl From<ClientPolicyRepresentation> for PolicyRepresentation {
+    fn from(value: ClientPolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "client");
+        set_policy_config_field(&mut policy, "clients", &value.clients);
+        policy
+    }
+}
+
+impl From<GroupPolicyRepresentation> for PolicyRepresentation {
+    fn from(value: GroupPolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "group");
+        set_policy_config_field(&mut policy, "groups", &value.groups);
+        policy
+            .config
+            .insert("groupsClaim".into(), value.groups_claim);
+        policy
+    }
+}
+
+impl From<JsPolicyRepresentation> for PolicyRepresentation {
+    fn from(value: JsPolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "js");
+        policy.config.insert("code".into(), value.code);
+        policy
+    }
+}
+
+impl From<RegexPolicyRepresentation> for PolicyRepresentation {
+    fn from(value: RegexPolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "regex");
+        policy
+            .config
+            .insert("targetClaim".into(), value.target_claim);
+        policy.config.insert("pattern".into(), value.pattern);
+        set_policy_config_field(
+            &mut policy,
+            "targetContextAttributes",
+            &value.target_context_attributes,
+        );
+        policy
+    }
+}
+
+impl From<RolePolicyRepresentation> for PolicyRepresentation {
+    fn from(value: RolePolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "role");
+        set_policy_config_field(&mut policy, "roles", &value.roles);
+        policy
+    }
+}
+
+impl From<UserPolicyRepresentation> for PolicyRepresentation {
+    fn from(value: UserPolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "user");
+        set_policy_config_field(&mut policy, "users", &value.users);
+        policy
+    }
+}
+
+impl From<ClientScopePolicyRepresentation> for PolicyRepresentation {
+    fn from(value: ClientScopePolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "client-scope");
+        set_policy_config_field(&mut policy, "clientScopes", &value.client_scopes);
+        policy
+    }
+}
+
+impl From<TimePolicyRepresentation> for PolicyRepresentation {
+    fn from(value: TimePolicyRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "time");
+        let fields = [
+            ("nbf", value.not_before),
+            ("noa", value.not_on_or_after),
+            ("dayMonth", value.day_month.map(|v| v.to_string())),
+            ("dayMonthEnd", value.day_month_end.map(|v| v.to_string())),
+            ("month", value.month.map(|v| v.to_string())),
+            ("monthEnd", value.month_end.map(|v| v.to_string())),
+            ("year", value.year.map(|v| v.to_string())),
+            ("yearEnd", value.year_end.map(|v| v.to_string())),
+            ("hour", value.hour.map(|v| v.to_string())),
+            ("hourEnd", value.hour_end.map(|v| v.to_string())),
+            ("minute", value.minute.map(|v| v.to_string())),
+            ("minuteEnd", value.minute_end.map(|v| v.to_string())),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                policy.config.insert(field.into(), value);
+            }
+        }
+        policy
+    }
+}
+
+impl From<ResourcePermissionRepresentation> for PolicyRepresentation {
+    fn from(value: ResourcePermissionRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "resource");
+        if let Some(resource_type) = value.resource_type {
+            policy
+                .config
+                .insert("defaultResourceType".into(), resource_type);
+        }
+        policy
+    }
+}
+
+impl From<ScopePermissionRepresentation> for PolicyRepresentation {
+    fn from(value: ScopePermissionRepresentation) -> Self {
+        let mut policy = into_policy(value.policy, "scope");
+        if let Some(resource_type) = value.resource_type {
+            policy
+                .config
+                .insert("defaultResourceType".into(), resource_type);
+        }
+        policy
+    }
+}
+
+/// a concrete subtype of [`PolicyRepresentation`] that can be created and updated directly
+pub trait TypedPolicy: Serialize + TryFrom<PolicyRepresentation, Error = Error> {
+    /// policy type used by keycloak (e.g. `role`)
+    const TYPE: &'static str;
+
+    /// the common fields of the policy
+    fn policy(&self) -> &PolicyRepresentation;
+}
+
+macro_rules! typed_policy {
+    ($($ty:ty => $name:literal),* $(,)?) => {$(
+        impl TypedPolicy for $ty {
+            const TYPE: &'static str = $name;
+
+            fn policy(&self) -> &PolicyRepresentation {
+                &self.policy
+            }
+        }
+    )*};
+}
+
+typed_policy! {
+    AggregatePolicyRepresentation => "aggregate",
+    ClientPolicyRepresentation => "client",
+    ClientScopePolicyRepresentation => "client-scope",
+    GroupPolicyRepresentation => "group",
+    JsPolicyRepresentation => "js",
+    RegexPolicyRepresentation => "regex",
+    RolePolicyRepresentation => "role",
+    TimePolicyRepresentation => "time",
+    UserPolicyRepresentation => "user",
+}
+
+/// a [`TypedPolicy`] that is managed using the permission endpoints
+pub trait TypedPermission: TypedPolicy {}
+
+typed_policy! {
+    ResourcePermissionRepresentation => "resource",
+    ScopePermissionRepresentation => "scope",
+}
+
+impl TypedPermission for ResourcePermissionRepresentation {}
+impl TypedPermission for ScopePermissionRepresentation {}
+
+/// a policy or permission of any type, using the typed representation matching its `type`
+///
+/// (de)serializes like a [`PolicyRepresentation`]. policies of types without a typed
+/// representation are kept as [`Policy::Unknown`], including their raw config.
+/// converting (or deserializing) only fails if the config of a known policy type is invalid.
+#[derive(Debug, Clone)]
+pub enum Policy {
+    Aggregate(AggregatePolicyRepresentation),
+    Client(ClientPolicyRepresentation),
+    ClientScope(ClientScopePolicyRepresentation),
+    Group(GroupPolicyRepresentation),
+    Js(JsPolicyRepresentation),
+    Regex(RegexPolicyRepresentation),
+    Role(RolePolicyRepresentation),
+    Time(TimePolicyRepresentation),
+    User(UserPolicyRepresentation),
+    ResourcePermission(ResourcePermissionRepresentation),
+    ScopePermission(ScopePermissionRepresentation),
+    Unknown(PolicyRepresentation),
+}
+
+macro_rules! policy_variants {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        impl Policy {
+            /// the common fields of the policy
+            pub fn policy(&self) -> &PolicyRepresentation {
+                match self {
+                    $(Self::$variant(policy) => policy.policy(),)*
+                    Self::Unknown(policy) => policy,
+                }
+            }
+        }
+
+        impl TryFrom<PolicyRepresentation> for Policy {
+            type Error = Error;
+
+            fn try_from(value: PolicyRepresentation) -> Result<Self, Self::Error> {
+                match value.type_.as_deref() {
+                    $(Some(policy_type) if policy_type == <$ty>::TYPE => {
+                        <$ty>::try_from(value).map(Self::$variant)
+                    })*
+                    _ => Ok(Self::Unknown(value)),
+                }
+            }
+        }
+
+        impl From<Policy> for PolicyRepresentation {
+            fn from(value: Policy) -> Self {
+                match value {
+                    $(Policy::$variant(policy) => policy.into(),)*
+                    Policy::Unknown(policy) => policy,
+                }
+            }
+        }
+    };
+}
+
+policy_variants! {
+    Aggregate(AggregatePolicyRepresentation),
+    Client(ClientPolicyRepresentation),
+    ClientScope(ClientScopePolicyRepresentation),
+    Group(GroupPolicyRepresentation),
+    Js(JsPolicyRepresentation),
+    Regex(RegexPolicyRepresentation),
+    Role(RolePolicyRepresentation),
+    Time(TimePolicyRepresentation),
+    User(UserPolicyRepresentation),
+    ResourcePermission(ResourcePermissionRepresentation),
+    ScopePermission(ScopePermissionRepresentation),
+}
+
+impl<'de> Deserialize<'de> for Policy {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let policy = PolicyRepresentation::deserialize(deserializer)?;
+        Self::try_from(policy).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Policy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PolicyRepresentation::from(self.clone()).serialize(serializer)
+    }
+}
+
+/// serialize a typed policy as expected by the `by-type` endpoints
+///
+/// the type-specific fields are sent as top-level fields instead of stringified `config` entries
+pub(crate) fn typed_policy_body<P: TypedPolicy>(
+    policy: &P,
+) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+    let serde_json::Value::Object(mut body) =
+        serde_json::to_value(policy).map_err(crate::error::deserialize)?
+    else {
+        return Err(Error::new_kind(ErrorKind::WrongType(
+            "object".into(),
+            "non-object".into(),
+        )));
+    };
+    body.insert("type".into(), P::TYPE.into());
+    Ok(body)
+}
+
 fn check_policy_type(
     policy: &PolicyRepresentation,
     expected_type: &'static str,
@@ -252,7 +704,7 @@ fn check_policy_config(policy: &mut PolicyRepresentation, expected_type: &'stati
             if !a.is_empty() {
                 a.push_str(", ")
             }
-            a.push_str(&b);
+            a.push_str(b);
             a
         });
         tracing::warn!(
@@ -263,6 +715,23 @@ fn check_policy_config(policy: &mut PolicyRepresentation, expected_type: &'stati
     policy.config.clear();
 }
 
+fn into_policy(
+    mut policy: PolicyRepresentation,
+    policy_type: &'static str,
+) -> PolicyRepresentation {
+    policy.type_ = Some(policy_type.into());
+    policy
+}
+
+fn set_policy_config_field<T: Serialize>(
+    policy: &mut PolicyRepresentation,
+    field: &'static str,
+    value: &T,
+) {
+    let value = serde_json::to_string(value).expect("BUG: policy config serialization failed");
+    policy.config.insert(field.into(), value);
+}
+
 fn get_policy_config_field(
     policy: &mut PolicyRepresentation,
     field: &'static str,
@@ -275,6 +744,19 @@ fn get_policy_config_field(
     Ok(value)
 }
 
+fn parse_policy_config_field<T: std::str::FromStr>(
+    policy: &mut PolicyRepresentation,
+    field: &'static str,
+) -> Result<Option<T>, Error> {
+    let Some(value) = policy.config.remove(field) else {
+        return Ok(None);
+    };
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| Error::new_kind(ErrorKind::WrongType("number".into(), value)))
+}
+
 fn deserialize_policy_config_field<T>(
     policy: &mut PolicyRepresentation,
     field: &'static str,
@@ -285,3 +767,190 @@ where
     let value = get_policy_config_field(policy, field)?;
     serde_json::from_str(&value).map_err(crate::error::deserialize)
 }
+
+/// (de)serialize numbers as strings, as keycloak's representations use strings for them
+mod string_number {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u32>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber {
+            String(String),
+            Number(u32),
+        }
+
+        match Option::<StringOrNumber>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(StringOrNumber::Number(value)) => Ok(Some(value)),
+            Some(StringOrNumber::String(value)) if value.is_empty() => Ok(None),
+            Some(StringOrNumber::String(value)) => {
+                value.parse().map(Some).map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_role_policy_builder() {
+        let policy: PolicyRepresentation = RolePolicyRepresentation::builder()
+            .name("admins")
+            .role("role-id", true)
+            .logic(Logic::Negative)
+            .build()
+            .into();
+        assert_eq!(policy.type_.as_deref(), Some("role"));
+        assert_eq!(policy.name.as_deref(), Some("admins"));
+        assert_eq!(policy.logic, Some(Logic::Negative));
+        assert_eq!(
+            policy.config["roles"],
+            r#"[{"id":"role-id","required":true}]"#
+        );
+
+        let policy = RolePolicyRepresentation::try_from(policy).unwrap();
+        assert_eq!(policy.roles.len(), 1);
+        assert_eq!(policy.roles[0].id, "role-id");
+        assert!(policy.roles[0].required);
+        assert!(policy.policy.config.is_empty());
+    }
+
+    /// convert a policy to its typed representation and back
+    fn round_trip<P>(policy: PolicyRepresentation)
+    where
+        P: TryFrom<PolicyRepresentation, Error = Error> + Into<PolicyRepresentation>,
+    {
+        let expected = serde_json::to_value(&policy).unwrap();
+        let typed = P::try_from(policy).unwrap();
+        let policy: PolicyRepresentation = typed.into();
+        assert_eq!(serde_json::to_value(&policy).unwrap(), expected);
+    }
+
+    fn policy(policy_type: &str, config: &[(&str, &str)]) -> PolicyRepresentation {
+        serde_json::from_value(serde_json::json!({
+            "id": "policy-id",
+            "name": "policy",
+            "type": policy_type,
+            "logic": "POSITIVE",
+            "decisionStrategy": "UNANIMOUS",
+            "config": config
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<std::collections::HashMap<_, _>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        round_trip::<AggregatePolicyRepresentation>(policy("aggregate", &[]));
+        round_trip::<ClientPolicyRepresentation>(policy("client", &[("clients", r#"["a","b"]"#)]));
+        round_trip::<ClientScopePolicyRepresentation>(policy(
+            "client-scope",
+            &[("clientScopes", r#"[{"id":"a","required":true}]"#)],
+        ));
+        round_trip::<GroupPolicyRepresentation>(policy(
+            "group",
+            &[
+                ("groups", r#"[{"id":"a","extendChildren":false}]"#),
+                ("groupsClaim", "groups"),
+            ],
+        ));
+        round_trip::<JsPolicyRepresentation>(policy("js", &[("code", "$evaluation.grant();")]));
+        round_trip::<RegexPolicyRepresentation>(policy(
+            "regex",
+            &[
+                ("targetClaim", "email"),
+                ("pattern", ".*@example\\.com"),
+                ("targetContextAttributes", "false"),
+            ],
+        ));
+        round_trip::<RolePolicyRepresentation>(policy(
+            "role",
+            &[("roles", r#"[{"id":"a","required":false}]"#)],
+        ));
+        round_trip::<TimePolicyRepresentation>(policy(
+            "time",
+            &[
+                ("nbf", "2024-01-01 00:00:00"),
+                ("hour", "8"),
+                ("hourEnd", "17"),
+            ],
+        ));
+        round_trip::<UserPolicyRepresentation>(policy("user", &[("users", r#"["a"]"#)]));
+        round_trip::<ResourcePermissionRepresentation>(policy(
+            "resource",
+            &[("defaultResourceType", "urn:example:resources:document")],
+        ));
+        round_trip::<ScopePermissionRepresentation>(policy("scope", &[]));
+    }
+
+    #[test]
+    fn test_policy_enum() {
+        let role = policy("role", &[("roles", r#"[{"id":"a","required":false}]"#)]);
+        let expected = serde_json::to_value(&role).unwrap();
+        let Policy::Role(typed) = serde_json::from_value(expected.clone()).unwrap() else {
+            panic!("expected role policy");
+        };
+        assert_eq!(typed.roles[0].id, "a");
+        assert_eq!(serde_json::to_value(Policy::Role(typed)).unwrap(), expected);
+
+        let custom = policy("custom", &[("key", "value")]);
+        let parsed = Policy::try_from(custom).unwrap();
+        let Policy::Unknown(ref unknown) = parsed else {
+            panic!("expected unknown policy");
+        };
+        assert_eq!(unknown.config["key"], "value");
+        assert_eq!(parsed.policy().name.as_deref(), Some("policy"));
+
+        let invalid = policy("role", &[("roles", "not json")]);
+        assert!(Policy::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn test_time_policy_body() {
+        let policy =
+            TimePolicyRepresentation::try_from(policy("time", &[("hour", "8"), ("hourEnd", "17")]))
+                .unwrap();
+        assert_eq!(policy.hour, Some(8));
+        let body = typed_policy_body(&policy).unwrap();
+        assert_eq!(body["hour"], "8");
+        assert_eq!(body["hourEnd"], "17");
+        assert!(!body.contains_key("minute"));
+    }
+
+    #[test]
+    fn test_typed_policy_body() {
+        let policy = RolePolicyRepresentation {
+            roles: vec![RolePolicyRepresentationRoleDefinition {
+                id: "role-id".into(),
+                required: true,
+            }],
+            policy: PolicyRepresentation::builder()
+                .name(Some("policy".into()))
+                .try_into()
+                .unwrap(),
+        };
+        let body = typed_policy_body(&policy).unwrap();
+        assert_eq!(
+            serde_json::Value::Object(body),
+            serde_json::json!({
+                "name": "policy",
+                "type": "role",
+                "roles": [{ "id": "role-id", "required": true }],
+            })
+        );
+    }
+}
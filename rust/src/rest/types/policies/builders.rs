@@ -0,0 +1,266 @@
+use crate::rest::types::*;
+
+/// setters for the fields shared by all policy types
+macro_rules! common_setters {
+    () => {
+        /// id of an existing policy, only needed for updates
+        pub fn id(mut self, id: &str) -> Self {
+            self.policy.id = Some(id.into());
+            self
+        }
+
+        /// unique name of the policy
+        pub fn name(mut self, name: &str) -> Self {
+            self.policy.name = Some(name.into());
+            self
+        }
+
+        pub fn description(mut self, description: &str) -> Self {
+            self.policy.description = Some(description.into());
+            self
+        }
+
+        /// whether the result of the policy is negated
+        pub fn logic(mut self, logic: Logic) -> Self {
+            self.policy.logic = Some(logic);
+            self
+        }
+    };
+}
+
+fn empty_policy() -> PolicyRepresentation {
+    crate::rest::types::builder::PolicyRepresentation::default()
+        .try_into()
+        .expect("BUG: policy representation conversion failed")
+}
+
+/// builder for [`AggregatePolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct AggregatePolicyBuilder {
+    policy: PolicyRepresentation,
+}
+
+impl AggregatePolicyRepresentation {
+    pub fn builder() -> AggregatePolicyBuilder {
+        AggregatePolicyBuilder {
+            policy: empty_policy(),
+        }
+    }
+}
+
+impl AggregatePolicyBuilder {
+    common_setters!();
+
+    /// add a policy (by id or name) to the aggregated policies
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy
+            .policies
+            .get_or_insert_with(Vec::new)
+            .push(policy.into());
+        self
+    }
+
+    /// how the results of the aggregated policies are combined
+    pub fn decision_strategy(mut self, decision_strategy: DecisionStrategy) -> Self {
+        self.policy.decision_strategy = Some(decision_strategy);
+        self
+    }
+
+    pub fn build(self) -> AggregatePolicyRepresentation {
+        AggregatePolicyRepresentation {
+            policy: self.policy,
+        }
+    }
+}
+
+/// builder for [`ClientPolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct ClientPolicyBuilder {
+    policy: PolicyRepresentation,
+    clients: Vec<String>,
+}
+
+impl ClientPolicyRepresentation {
+    pub fn builder() -> ClientPolicyBuilder {
+        ClientPolicyBuilder {
+            policy: empty_policy(),
+            clients: Vec::new(),
+        }
+    }
+}
+
+impl ClientPolicyBuilder {
+    common_setters!();
+
+    /// grant access to a client, given its uuid
+    pub fn client(mut self, client_uuid: &str) -> Self {
+        self.clients.push(client_uuid.into());
+        self
+    }
+
+    pub fn build(self) -> ClientPolicyRepresentation {
+        ClientPolicyRepresentation {
+            clients: self.clients,
+            policy: self.policy,
+        }
+    }
+}
+
+/// builder for [`GroupPolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct GroupPolicyBuilder {
+    policy: PolicyRepresentation,
+    groups: Vec<GroupPolicyRepresentationGroupDefinition>,
+    groups_claim: String,
+}
+
+impl GroupPolicyRepresentation {
+    pub fn builder() -> GroupPolicyBuilder {
+        GroupPolicyBuilder {
+            policy: empty_policy(),
+            groups: Vec::new(),
+            groups_claim: String::new(),
+        }
+    }
+}
+
+impl GroupPolicyBuilder {
+    common_setters!();
+
+    /// grant access to the members of a group, optionally including the members of its subgroups
+    pub fn group(mut self, group_id: &str, extend_children: bool) -> Self {
+        self.groups.push(GroupPolicyRepresentationGroupDefinition {
+            id: group_id.into(),
+            extend_children,
+        });
+        self
+    }
+
+    /// claim of the token containing the group paths of the user
+    ///
+    /// if unset, the groups are taken from the user's group memberships instead
+    pub fn groups_claim(mut self, groups_claim: &str) -> Self {
+        self.groups_claim = groups_claim.into();
+        self
+    }
+
+    pub fn build(self) -> GroupPolicyRepresentation {
+        GroupPolicyRepresentation {
+            groups: self.groups,
+            groups_claim: self.groups_claim,
+            policy: self.policy,
+        }
+    }
+}
+
+/// builder for [`RegexPolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct RegexPolicyBuilder {
+    policy: PolicyRepresentation,
+    target_claim: String,
+    pattern: String,
+    target_context_attributes: bool,
+}
+
+impl RegexPolicyRepresentation {
+    /// the policy grants access if the value of `target_claim` matches `pattern`
+    pub fn builder(target_claim: &str, pattern: &str) -> RegexPolicyBuilder {
+        RegexPolicyBuilder {
+            policy: empty_policy(),
+            target_claim: target_claim.into(),
+            pattern: pattern.into(),
+            target_context_attributes: false,
+        }
+    }
+}
+
+impl RegexPolicyBuilder {
+    common_setters!();
+
+    /// match the target claim against the context attributes of the request instead of the
+    /// identity's claims
+    pub fn target_context_attributes(mut self, target_context_attributes: bool) -> Self {
+        self.target_context_attributes = target_context_attributes;
+        self
+    }
+
+    pub fn build(self) -> RegexPolicyRepresentation {
+        RegexPolicyRepresentation {
+            target_claim: self.target_claim,
+            pattern: self.pattern,
+            target_context_attributes: self.target_context_attributes,
+            policy: self.policy,
+        }
+    }
+}
+
+/// builder for [`RolePolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct RolePolicyBuilder {
+    policy: PolicyRepresentation,
+    roles: Vec<RolePolicyRepresentationRoleDefinition>,
+}
+
+impl RolePolicyRepresentation {
+    pub fn builder() -> RolePolicyBuilder {
+        RolePolicyBuilder {
+            policy: empty_policy(),
+            roles: Vec::new(),
+        }
+    }
+}
+
+impl RolePolicyBuilder {
+    common_setters!();
+
+    /// grant access to users with a role, given its id
+    ///
+    /// if any role is `required`, the user must have all required roles
+    pub fn role(mut self, role_id: &str, required: bool) -> Self {
+        self.roles.push(RolePolicyRepresentationRoleDefinition {
+            id: role_id.into(),
+            required,
+        });
+        self
+    }
+
+    pub fn build(self) -> RolePolicyRepresentation {
+        RolePolicyRepresentation {
+            roles: self.roles,
+            policy: self.policy,
+        }
+    }
+}
+
+/// builder for [`UserPolicyRepresentation`]
+#[derive(Debug, Clone)]
+pub struct UserPolicyBuilder {
+    policy: PolicyRepresentation,
+    users: Vec<String>,
+}
+
+impl UserPolicyRepresentation {
+    pub fn builder() -> UserPolicyBuilder {
+        UserPolicyBuilder {
+            policy: empty_policy(),
+            users: Vec::new(),
+        }
+    }
+}
+
+impl UserPolicyBuilder {
+    common_setters!();
+
+    /// grant access to a user, given its id
+    pub fn user(mut self, user_id: &str) -> Self {
+        self.users.push(user_id.into());
+        self
+    }
+
+    pub fn build(self) -> UserPolicyRepresentation {
+        UserPolicyRepresentation {
+            users: self.users,
+            policy: self.policy,
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! smoke tests for the public login flows of a realm
+//!
+//! the admin api happily accepts flow and user profile configurations that break the login pages
+//! for actual users. the helpers in this module drive keycloak's public endpoints the same way a
+//! browser does (plain form posts, no javascript) so post-deploy checks can verify that
+//! self-registration and password reset still work end-to-end.
+
+use std::time::Duration;
+
+use reqwest::{header, StatusCode};
+
+use crate::{Error, ErrorKind, KeycloakConfig};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// values submitted in the self-registration form
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationForm {
+    pub username: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub password: String,
+    /// additional form fields, e.g. custom user profile attributes
+    pub extra_fields: Vec<(String, String)>,
+}
+
+/// runs the public login flows of a realm against a running keycloak instance
+///
+/// every method starts a fresh browser-like session, so a single instance can be reused for
+/// multiple checks.
+pub struct FlowSmokeTest {
+    config: KeycloakConfig,
+    client_id: String,
+    redirect_uri: String,
+}
+
+impl FlowSmokeTest {
+    /// `client_id` must reference a client allowing the standard flow with `redirect_uri` as valid
+    /// redirect uri
+    pub fn new(config: &KeycloakConfig, client_id: &str, redirect_uri: &str) -> Self {
+        Self {
+            config: config.clone(),
+            client_id: client_id.into(),
+            redirect_uri: redirect_uri.into(),
+        }
+    }
+
+    /// register a new user using the realm's self-registration form
+    ///
+    /// succeeds if keycloak redirects back to the client with an authorization code after
+    /// submitting the form. the registered user is not removed afterwards.
+    #[tracing::instrument(skip(self, form), fields(username = form.username))]
+    pub async fn registration(&self, form: &RegistrationForm) -> Result<()> {
+        let session = Session::new();
+
+        tracing::debug!("opening registration page");
+        let url = format!(
+            "{}/realms/{}/protocol/openid-connect/registrations",
+            self.config.base_url, self.config.realm
+        );
+        let request = session.client.get(url).query(&[
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", "openid"),
+        ]);
+        let page = session.page(request).await?;
+        let Some(action) = form_action(&page, "kc-register-form") else {
+            return Err(flow_error(
+                "registration form not found (is user registration enabled?)",
+            ));
+        };
+
+        tracing::debug!("submitting registration form");
+        let mut fields = vec![
+            ("username", form.username.as_str()),
+            ("email", form.email.as_str()),
+            ("firstName", form.first_name.as_str()),
+            ("lastName", form.last_name.as_str()),
+            ("password", form.password.as_str()),
+            ("password-confirm", form.password.as_str()),
+        ];
+        fields.extend(
+            form.extra_fields
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        );
+        let response = session
+            .send(session.client.post(action).form(&fields))
+            .await?;
+        self.expect_code_redirect(response).await
+    }
+
+    /// request a password reset email for the given username (or email)
+    ///
+    /// succeeds if keycloak accepts the reset form without showing an error. note that keycloak
+    /// doesn't reveal whether the user exists, so this only checks the flow itself (including
+    /// sending the email).
+    #[tracing::instrument(skip(self))]
+    pub async fn reset_password(&self, username: &str) -> Result<()> {
+        let session = Session::new();
+
+        tracing::debug!("opening login page");
+        let url = format!(
+            "{}/realms/{}/protocol/openid-connect/auth",
+            self.config.base_url, self.config.realm
+        );
+        let request = session.client.get(url).query(&[
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", "openid"),
+        ]);
+        let page = session.page(request).await?;
+        let Some(reset_url) = link(&page, "/login-actions/reset-credentials") else {
+            return Err(flow_error(
+                "password reset link not found (is forgot password enabled?)",
+            ));
+        };
+
+        tracing::debug!("opening password reset page");
+        let page = session.page(session.client.get(reset_url)).await?;
+        let Some(action) = form_action(&page, "kc-reset-password-form") else {
+            return Err(flow_error("password reset form not found"));
+        };
+
+        tracing::debug!("submitting password reset form");
+        let response = session
+            .send(session.client.post(action).form(&[("username", username)]))
+            .await?;
+        if response.status().is_redirection() {
+            return Ok(());
+        }
+        let status = response.status();
+        let page = response.text().await.map_err(crate::error::reqwest)?;
+        if !status.is_success() {
+            return Err(flow_error(format!(
+                "password reset form returned status {status}"
+            )));
+        }
+        if let Some(message) = error_message(&page) {
+            return Err(flow_error(format!("password reset failed: {message}")));
+        }
+        Ok(())
+    }
+
+    async fn expect_code_redirect(&self, response: reqwest::Response) -> Result<()> {
+        if let Some(location) = redirect_location(&response) {
+            if location.starts_with(&self.redirect_uri) && location.contains("code=") {
+                return Ok(());
+            }
+            return Err(flow_error(format!("unexpected redirect to {location}")));
+        }
+        let status = response.status();
+        let page = response.text().await.map_err(crate::error::reqwest)?;
+        let message = error_message(&page).unwrap_or_else(|| format!("status {status}"));
+        Err(flow_error(format!("flow did not complete: {message}")))
+    }
+}
+
+/// a single browser session
+///
+/// keycloak tracks the authentication session using cookies, so every flow gets its own client
+/// with a separate cookie store
+struct Session {
+    client: reqwest::Client,
+}
+
+impl Session {
+    fn new() -> Self {
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30))
+            .cookie_store(true)
+            // we need to see the redirects to follow the flow
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("BUG: reqwest client builder failed");
+        Self { client }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        request.send().await.map_err(crate::error::reqwest)
+    }
+
+    /// load an html page, following redirects
+    async fn page(&self, request: reqwest::RequestBuilder) -> Result<String> {
+        const MAX_REDIRECTS: usize = 10;
+
+        let mut response = self.send(request).await?;
+        for _ in 0..MAX_REDIRECTS {
+            let Some(location) = redirect_location(&response) else {
+                break;
+            };
+            let location = response
+                .url()
+                .join(&location)
+                .map_err(|e| flow_error(format!("invalid redirect location: {e}")))?;
+            response = self.send(self.client.get(location)).await?;
+        }
+        if response.status() != StatusCode::OK {
+            return Err(crate::error::error_response(response).await);
+        }
+        response.text().await.map_err(crate::error::reqwest)
+    }
+}
+
+fn flow_error(message: impl Into<String>) -> Error {
+    Error::new_kind(ErrorKind::FlowCheck(message.into()))
+}
+
+fn redirect_location(response: &reqwest::Response) -> Option<String> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|l| l.to_str().ok())
+        .map(Into::into)
+}
+
+/// find the `action` of the html form with the given id
+fn form_action(page: &str, form_id: &str) -> Option<String> {
+    let id_pos = page.find(&format!("id=\"{form_id}\""))?;
+    let tag_start = page[..id_pos].rfind("<form")?;
+    let tag_end = id_pos + page[id_pos..].find('>')?;
+    attribute(&page[tag_start..tag_end], "action")
+}
+
+/// find the first link containing `path` in its target
+fn link(page: &str, path: &str) -> Option<String> {
+    let mut rest = page;
+    while let Some(pos) = rest.find("href=\"") {
+        rest = &rest[pos..];
+        let href = attribute(rest, "href")?;
+        if href.contains(path) {
+            return Some(href);
+        }
+        rest = &rest[1..];
+    }
+    None
+}
+
+/// get the error message keycloak rendered into a page (if any)
+fn error_message(page: &str) -> Option<String> {
+    if !(page.contains("alert-error") || page.contains("pf-m-danger")) {
+        return None;
+    }
+    let start = page.find("kc-feedback-text")?;
+    let start = start + page[start..].find('>')? + 1;
+    let end = start + page[start..].find('<')?;
+    Some(unescape(page[start..end].trim()))
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!("{name}=\"");
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = start + tag[start..].find('"')?;
+    Some(unescape(&tag[start..end]))
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_form_parsing() {
+        let page = r#"<html><body>
+            <a tabindex="6" href="https://kc/realms/test/login-actions/reset-credentials?client_id=app&amp;tab_id=abc">forgot password?</a>
+            <form id="kc-register-form" class="form" action="https://kc/realms/test/login-actions/registration?session_code=x&amp;execution=y&amp;client_id=app" method="post">
+            <span class="pf-c-alert alert-error"></span><span class="kc-feedback-text">Email already exists.</span>
+            </form></body></html>"#;
+
+        assert_eq!(
+            super::form_action(page, "kc-register-form").as_deref(),
+            Some("https://kc/realms/test/login-actions/registration?session_code=x&execution=y&client_id=app")
+        );
+        assert_eq!(super::form_action(page, "kc-reset-password-form"), None);
+        assert_eq!(
+            super::link(page, "/login-actions/reset-credentials").as_deref(),
+            Some("https://kc/realms/test/login-actions/reset-credentials?client_id=app&tab_id=abc")
+        );
+        assert_eq!(
+            super::error_message(page).as_deref(),
+            Some("Email already exists.")
+        );
+    }
+}
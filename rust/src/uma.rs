@@ -0,0 +1,352 @@
+//! client for the permission tickets of keycloak's uma protection api
+//!
+//! permission tickets represent requests of users to access resources owned by other users
+//! (user-managed access). the protection api belongs to a resource server (a client with
+//! authorization enabled) and is authenticated with a protection api token, i.e. an access token
+//! of the resource server obtained using the client credentials grant.
+
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::{auth::AuthenticationProvider, error::ResourceType, Error, ErrorKind, KeycloakConfig};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// a permission ticket
+///
+/// when creating tickets, resource, scope and requester can be given either by id or by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionTicket {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// id of the owner of the resource
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// id of the resource
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    /// id of the requested scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// whether the owner granted the request
+    #[serde(default)]
+    pub granted: bool,
+    /// id of the user requesting access
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requester: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requester_name: Option<String>,
+}
+
+/// filters for [`tickets`](UmaProtection::tickets) and
+/// [`ticket_count`](UmaProtection::ticket_count)
+///
+/// all filters are combined, so only tickets matching every filter are returned
+#[derive(Debug, Clone, Default)]
+pub struct TicketQuery {
+    resource_id: Option<String>,
+    scope_id: Option<String>,
+    owner: Option<String>,
+    requester: Option<String>,
+    granted: Option<bool>,
+    return_names: Option<bool>,
+}
+
+impl TicketQuery {
+    /// no filters, matches all tickets of the resource server
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resource_id(mut self, resource_id: &str) -> Self {
+        self.resource_id = Some(resource_id.into());
+        self
+    }
+
+    pub fn scope_id(mut self, scope_id: &str) -> Self {
+        self.scope_id = Some(scope_id.into());
+        self
+    }
+
+    /// id of the user owning the resources
+    pub fn owner(mut self, owner: &str) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// id of the user requesting access
+    pub fn requester(mut self, requester: &str) -> Self {
+        self.requester = Some(requester.into());
+        self
+    }
+
+    /// only return granted (or pending) tickets
+    pub fn granted(mut self, granted: bool) -> Self {
+        self.granted = Some(granted);
+        self
+    }
+
+    /// also fill in the names of owner, resource, scope and requester
+    pub fn return_names(mut self, return_names: bool) -> Self {
+        self.return_names = Some(return_names);
+        self
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        let strings = [
+            ("resourceId", &self.resource_id),
+            ("scopeId", &self.scope_id),
+            ("owner", &self.owner),
+            ("requester", &self.requester),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                params.push((name, value.clone()));
+            }
+        }
+        let bools = [
+            ("granted", self.granted),
+            ("returnNames", self.return_names),
+        ];
+        for (name, value) in bools {
+            if let Some(value) = value {
+                params.push((name, value.to_string()));
+            }
+        }
+        params
+    }
+}
+
+/// client for the permission tickets of a resource server
+pub struct UmaProtection<A: AuthenticationProvider> {
+    config: KeycloakConfig,
+    client: reqwest::Client,
+    auth: RwLock<A>,
+}
+
+impl<A: AuthenticationProvider> UmaProtection<A> {
+    /// `auth` has to provide protection api tokens of the resource server
+    ///
+    /// protection api tokens are short-lived, so the token is refreshed before a request if
+    /// `auth` supports it (see [`AuthenticationProvider::can_refresh`])
+    pub async fn new(config: &KeycloakConfig, mut auth: A) -> Result<Self> {
+        auth.login(config).await?;
+        if !auth.token_is_valid() || auth.access_token().is_none() {
+            return Err(Error::new_kind(ErrorKind::MissingAccessToken));
+        }
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("BUG: reqwest client builder failed");
+        Ok(Self {
+            config: config.clone(),
+            client,
+            auth: RwLock::new(auth),
+        })
+    }
+
+    /// start a request authenticated with the current protection api token, refreshing it first
+    /// if necessary
+    async fn request(&self, method: Method, url: reqwest::Url) -> Result<reqwest::RequestBuilder> {
+        let auth = self.auth.read().await;
+        let auth = if auth.needs_refresh() && auth.can_refresh() {
+            drop(auth);
+            tracing::debug!("refreshing protection api token");
+            let mut auth = self.auth.write().await;
+            // another request might have refreshed the token in the meantime
+            if auth.needs_refresh() {
+                auth.refresh(&self.config).await?;
+            }
+            auth.downgrade()
+        } else {
+            auth
+        };
+        if !auth.token_is_valid() {
+            return Err(Error::new_kind(ErrorKind::TokenExpired));
+        }
+        let Some(access_token) = auth.access_token() else {
+            return Err(Error::new_kind(ErrorKind::MissingAccessToken));
+        };
+        // invalid tokens (e.g. containing a newline) fail when sending the request
+        Ok(self.client.request(method, url).bearer_auth(access_token))
+    }
+
+    /// get a page of the permission tickets matching the query
+    #[tracing::instrument(skip(self))]
+    pub async fn tickets(
+        &self,
+        query: &TicketQuery,
+        first: Option<i32>,
+        max: Option<i32>,
+    ) -> Result<Vec<PermissionTicket>> {
+        let mut params = query.params();
+        params.extend(first.map(|first| ("first", first.to_string())));
+        params.extend(max.map(|max| ("max", max.to_string())));
+
+        tracing::debug!("querying permission tickets");
+        let response = self
+            .request(Method::GET, self.url(&[])?)
+            .await?
+            .query(&params)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+    }
+
+    /// get the number of permission tickets matching the query
+    #[tracing::instrument(skip(self))]
+    pub async fn ticket_count(&self, query: &TicketQuery) -> Result<u64> {
+        tracing::debug!("counting permission tickets");
+        let response = self
+            .request(Method::GET, self.url(&["count"])?)
+            .await?
+            .query(&query.params())
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await);
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+    }
+
+    /// create a permission ticket on behalf of a requester
+    ///
+    /// the ticket is granted immediately if `granted` is set
+    #[tracing::instrument(skip(self))]
+    pub async fn create_ticket(&self, ticket: &PermissionTicket) -> Result<PermissionTicket> {
+        tracing::debug!("creating permission ticket");
+        let response = self
+            .request(Method::POST, self.url(&[])?)
+            .await?
+            .json(ticket)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(crate::error::error_response(response).await.map_status(
+                StatusCode::CONFLICT,
+                ErrorKind::Conflict(ResourceType::PermissionTicket),
+            ));
+        }
+        let bytes = response.bytes().await.map_err(crate::error::reqwest)?;
+        serde_json::from_slice(&bytes).map_err(crate::error::deserialize)
+    }
+
+    /// update a permission ticket, the ticket's `id` must be set
+    #[tracing::instrument(skip(self))]
+    pub async fn update_ticket(&self, ticket: &PermissionTicket) -> Result<()> {
+        if ticket.id.is_none() {
+            return Err(Error::new_kind(ErrorKind::MissingId));
+        }
+
+        tracing::debug!("updating permission ticket");
+        let response = self
+            .request(Method::PUT, self.url(&[])?)
+            .await?
+            .json(ticket)
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(not_found(crate::error::error_response(response).await));
+        }
+        Ok(())
+    }
+
+    /// delete a permission ticket
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_ticket(&self, ticket_id: &str) -> Result<()> {
+        tracing::debug!("deleting permission ticket");
+        let response = self
+            .request(Method::DELETE, self.url(&[ticket_id])?)
+            .await?
+            .send()
+            .await
+            .map_err(crate::error::reqwest)?;
+        if !response.status().is_success() {
+            return Err(not_found(crate::error::error_response(response).await));
+        }
+        Ok(())
+    }
+
+    /// grant the access request of a permission ticket
+    #[tracing::instrument(skip(self))]
+    pub async fn grant_ticket(&self, ticket_id: &str) -> Result<()> {
+        let ticket = PermissionTicket {
+            id: Some(ticket_id.into()),
+            granted: true,
+            ..Default::default()
+        };
+        self.update_ticket(&ticket).await
+    }
+
+    /// deny the access request of a permission ticket (or revoke a granted one)
+    ///
+    /// keycloak has no separate state for denied requests, so this deletes the ticket
+    #[tracing::instrument(skip(self))]
+    pub async fn deny_ticket(&self, ticket_id: &str) -> Result<()> {
+        self.delete_ticket(ticket_id).await
+    }
+
+    fn url(&self, segments: &[&str]) -> Result<reqwest::Url> {
+        self.config.url(
+            [
+                "realms",
+                &self.config.realm,
+                "authz",
+                "protection",
+                "permission",
+                "ticket",
+            ]
+            .into_iter()
+            .chain(segments.iter().copied()),
+        )
+    }
+}
+
+fn not_found(e: Error) -> Error {
+    e.map_status(
+        StatusCode::NOT_FOUND,
+        ErrorKind::NotFound(ResourceType::PermissionTicket),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_ticket_query_params() {
+        assert!(super::TicketQuery::new().params().is_empty());
+
+        let query = super::TicketQuery::new()
+            .resource_id("resource")
+            .requester("user")
+            .granted(false);
+        assert_eq!(
+            query.params(),
+            [
+                ("resourceId", "resource".to_string()),
+                ("requester", "user".to_string()),
+                ("granted", "false".to_string()),
+            ]
+        );
+    }
+}
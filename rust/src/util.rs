@@ -21,3 +21,77 @@ where
         self(client)
     }
 }
+
+/// helper for the generated request builders, which only accept concrete values for optional
+/// parameters
+pub(crate) trait RequestBuilderExt: Sized {
+    /// call `set` with the given value if it is `Some`
+    fn set_opt<T>(self, value: Option<T>, set: impl FnOnce(Self, T) -> Self) -> Self {
+        match value {
+            Some(value) => set(self, value),
+            None => self,
+        }
+    }
+}
+
+impl<T> RequestBuilderExt for T {}
+
+/// merge the fields set in `update` into `existing`
+///
+/// the merge happens on the json representations: nested objects (e.g. attributes) are merged
+/// recursively, everything else is replaced by the value from `update`. as the generated types skip
+/// unset fields when serializing, this only touches fields actually set in `update`.
+pub(crate) fn merge_representation<T>(existing: &T, update: &T) -> Result<T, crate::Error>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn merge(existing: &mut serde_json::Value, update: serde_json::Value) {
+        match (existing, update) {
+            (serde_json::Value::Object(existing), serde_json::Value::Object(update)) => {
+                for (key, value) in update {
+                    match existing.get_mut(&key) {
+                        Some(existing) => merge(existing, value),
+                        None => {
+                            existing.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (existing, update) => *existing = update,
+        }
+    }
+
+    let mut merged = serde_json::to_value(existing).map_err(crate::error::deserialize)?;
+    let update = serde_json::to_value(update).map_err(crate::error::deserialize)?;
+    merge(&mut merged, update);
+    serde_json::from_value(merged).map_err(crate::error::deserialize)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_merge_representation() {
+        let existing: crate::rest::types::UserRepresentation =
+            serde_json::from_value(serde_json::json!({
+                "id": "1",
+                "username": "user",
+                "firstName": "old",
+                "attributes": { "a": ["1"], "b": ["2"] },
+            }))
+            .unwrap();
+        let update = serde_json::from_value(serde_json::json!({
+            "firstName": "new",
+            "enabled": false,
+            "attributes": { "b": ["3", "4"] },
+        }))
+        .unwrap();
+
+        let merged = super::merge_representation(&existing, &update).unwrap();
+        assert_eq!(merged.id.as_deref(), Some("1"));
+        assert_eq!(merged.username.as_deref(), Some("user"));
+        assert_eq!(merged.first_name.as_deref(), Some("new"));
+        assert_eq!(merged.enabled, Some(false));
+        assert_eq!(merged.attributes["a"], ["1"]);
+        assert_eq!(merged.attributes["b"], ["3", "4"]);
+    }
+}